@@ -0,0 +1,182 @@
+use crate::{mean, Broadcast, Error, Mode, Mppt, Status};
+use bxcan::Frame;
+
+/// Group of MPPT devices sharing a bus.
+#[derive(Debug, Clone, Copy)]
+pub struct MpptArray<const N: usize> {
+    devices: [Mppt; N],
+}
+
+impl<const N: usize> MpptArray<N> {
+    /// Create a new array from individually configured devices.
+    pub fn new(devices: [Mppt; N]) -> Self {
+        Self { devices }
+    }
+
+    /// Get a device by its index in the array.
+    pub fn get(&self, index: usize) -> Option<&Mppt> {
+        self.devices.get(index)
+    }
+
+    /// Iterate over the base identifier (as returned by [`Mppt::base_id`]) and
+    /// status of every device.
+    pub fn iter(&self) -> impl Iterator<Item = (u16, &Status)> {
+        self.devices.iter().map(|d| (d.base_id(), &d.status))
+    }
+
+    /// Process an incoming message on the device it is addressed to.
+    ///
+    /// Returns the telemetry group that was updated, or `None` if the message
+    /// identifier does not match any device.
+    pub fn receive(&mut self, frame: &Frame) -> Result<Option<Broadcast>, Error> {
+        match self.devices.iter_mut().find(|d| d.is_for_device(frame)) {
+            Some(device) => device.receive(frame),
+            None => Ok(None),
+        }
+    }
+
+    /// Process an incoming message received at `now_ms` on the device it is
+    /// addressed to.
+    ///
+    /// See [`Mppt::receive_at`].
+    pub fn receive_at(&mut self, frame: &Frame, now_ms: u32) -> Result<Option<Broadcast>, Error> {
+        match self.devices.iter_mut().find(|d| d.is_for_device(frame)) {
+            Some(device) => device.receive_at(frame, now_ms),
+            None => Ok(None),
+        }
+    }
+
+    /// Number of devices that have sent telemetry within `timeout_ms`.
+    ///
+    /// See [`Mppt::is_online`].
+    pub fn online_count(&self, now_ms: u32, timeout_ms: u32) -> usize {
+        self.devices
+            .iter()
+            .filter(|d| d.is_online(now_ms, timeout_ms))
+            .count()
+    }
+
+    /// Energy delivered by every device in watt hours.
+    ///
+    /// See [`Mppt::energy_harvested_wh`].
+    pub fn total_energy_harvested(&self) -> f32 {
+        self.devices.iter().map(Mppt::energy_harvested_wh).sum()
+    }
+
+    /// Set the operating mode of every device.
+    ///
+    /// Returns one command frame per device, in array order.
+    pub fn set_mode_all(&mut self, mode: Mode) -> [Frame; N] {
+        self.devices.each_mut().map(|d| d.set_mode(mode))
+    }
+
+    /// Fraction of the total array output power produced by a device.
+    ///
+    /// Returns `None` if the index is out of range, any device has not
+    /// reported its output voltage and current, or the total output power is
+    /// zero.
+    pub fn contribution(&self, index: usize) -> Option<f32> {
+        let power = |d: &Mppt| d.status.output_power();
+
+        let device = power(self.devices.get(index)?)?;
+        let total = self
+            .devices
+            .iter()
+            .map(power)
+            .try_fold(0.0, |total, p| Some(total + p?))?;
+
+        if total == 0.0 {
+            return None;
+        }
+
+        Some(device / total)
+    }
+
+    /// Combine the status of every device into a single array level status.
+    ///
+    /// Fields are aggregated from the devices where the value is present:
+    ///
+    /// - currents (including the maximum input current) are summed,
+    /// - voltages (including the rails and maximum output voltage) are
+    ///   averaged,
+    /// - temperatures take the maximum,
+    /// - error and limit flags (and their raw bytes) are combined with a
+    ///   bitwise OR,
+    /// - CAN error counts are summed, saturating at `u8::MAX`,
+    /// - the vendor specific uptime takes the maximum and lifetime energy is
+    ///   summed,
+    /// - the mode is only present when every reporting device agrees,
+    /// - the test counter is not aggregated and is always `None`.
+    pub fn combined_status(&self) -> Status {
+        let statuses = self.devices.map(|d| d.status);
+        let history = &statuses[..];
+
+        Status {
+            input_voltage: mean(history, |s| s.input_voltage),
+            input_current: sum(history, |s| s.input_current),
+            output_voltage: mean(history, |s| s.output_voltage),
+            output_current: sum(history, |s| s.output_current),
+            mostfet_temperature: max(history, |s| s.mostfet_temperature),
+            controller_temperature: max(history, |s| s.controller_temperature),
+            rail_12v: mean(history, |s| s.rail_12v),
+            rail_3v: mean(history, |s| s.rail_3v),
+            maximum_output_voltage: mean(history, |s| s.maximum_output_voltage),
+            maximum_input_current: sum(history, |s| s.maximum_input_current),
+            can_rx_error_count: count(history, |s| s.can_rx_error_count),
+            can_tx_error_count: count(history, |s| s.can_tx_error_count),
+            can_tx_overflow_count: count(history, |s| s.can_tx_overflow_count),
+            error_flags: history
+                .iter()
+                .filter_map(|s| s.error_flags)
+                .reduce(|a, b| a | b),
+            limit_flags: history
+                .iter()
+                .filter_map(|s| s.limit_flags)
+                .reduce(|a, b| a | b),
+            raw_error_byte: history
+                .iter()
+                .filter_map(|s| s.raw_error_byte)
+                .reduce(|a, b| a | b),
+            raw_limit_byte: history
+                .iter()
+                .filter_map(|s| s.raw_limit_byte)
+                .reduce(|a, b| a | b),
+            mode: mode(history, |s| s.mode),
+            test_counter: None,
+            power_connector_voltage: mean(history, |s| s.power_connector_voltage),
+            power_connector_temperature: max(history, |s| s.power_connector_temperature),
+            commanded_mode: mode(history, |s| s.commanded_mode),
+            commanded_max_output_voltage: mean(history, |s| s.commanded_max_output_voltage),
+            commanded_max_input_current: sum(history, |s| s.commanded_max_input_current),
+            #[cfg(feature = "vendor-extended-status")]
+            uptime_s: history.iter().filter_map(|s| s.uptime_s).max(),
+            #[cfg(feature = "vendor-extended-status")]
+            lifetime_energy_wh: sum(history, |s| s.lifetime_energy_wh),
+        }
+    }
+}
+
+fn sum(history: &[Status], field: impl Fn(&Status) -> Option<f32>) -> Option<f32> {
+    history.iter().filter_map(field).reduce(|a, b| a + b)
+}
+
+fn max(history: &[Status], field: impl Fn(&Status) -> Option<f32>) -> Option<f32> {
+    history.iter().filter_map(field).reduce(f32::max)
+}
+
+fn count(history: &[Status], field: impl Fn(&Status) -> Option<u8>) -> Option<u8> {
+    history
+        .iter()
+        .filter_map(field)
+        .reduce(|a, b| a.saturating_add(b))
+}
+
+fn mode(history: &[Status], field: impl Fn(&Status) -> Option<Mode>) -> Option<Mode> {
+    let mut modes = history.iter().filter_map(field);
+    let first = modes.next()?;
+
+    match modes.all(|m| m == first) {
+        true => Some(first),
+        false => None,
+    }
+}
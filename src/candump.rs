@@ -0,0 +1,144 @@
+use bxcan::{Data, Frame, StandardId};
+
+use crate::{Mppt, Status, BROADCAST_COUNT};
+
+/// Parse a line of `candump` output into an identifier, payload and DLC.
+///
+/// Lines are expected in the default `candump` format, optionally preceded by
+/// a timestamp:
+///
+/// ```text
+/// can0  600   [8]  00 11 22 33 44 55 66 77
+/// ```
+///
+/// Bytes beyond the DLC are zero. Returns `None` for extended identifiers,
+/// remote frames or malformed lines.
+pub fn parse_candump_line(line: &str) -> Option<(u16, [u8; 8], usize)> {
+    let mut tokens = line.split_whitespace();
+
+    // the identifier is the token before the bracketed DLC
+    let mut id = tokens.next()?;
+    let dlc = loop {
+        let token = tokens.next()?;
+        if let Some(dlc) = token.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+            break dlc.parse::<usize>().ok()?;
+        }
+        id = token;
+    };
+
+    if id.len() > 3 || dlc > 8 {
+        return None;
+    }
+    let id = u16::from_str_radix(id, 16).ok()?;
+
+    let mut data = [0u8; 8];
+    for byte in data.iter_mut().take(dlc) {
+        *byte = u8::from_str_radix(tokens.next()?, 16).ok()?;
+    }
+
+    if tokens.next().is_some() {
+        return None;
+    }
+
+    Some((id, data, dlc))
+}
+
+/// Names of the floating point fields compared by [`compare_candump_logs`].
+const FIELDS: [&str; 14] = [
+    "input_voltage",
+    "input_current",
+    "output_voltage",
+    "output_current",
+    "mostfet_temperature",
+    "controller_temperature",
+    "rail_12v",
+    "rail_3v",
+    "maximum_output_voltage",
+    "maximum_input_current",
+    "power_connector_voltage",
+    "power_connector_temperature",
+    "commanded_max_output_voltage",
+    "commanded_max_input_current",
+];
+
+/// Difference of a status field between two decoded logs.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Deviation {
+    /// Name of the `Status` field.
+    pub field: &'static str,
+    /// Mean of the difference `after - before`.
+    pub mean: f32,
+    /// Largest absolute difference.
+    pub max: f32,
+    /// Number of compared frames where the field was present in both logs.
+    pub samples: usize,
+}
+
+/// Compare the telemetry decoded from two `candump` logs of a device.
+///
+/// Each log is decoded with [`Mppt::peek`] for a device at `base_id`. The
+/// frames of each telemetry group are compared in order between the two logs,
+/// on only the fields the group carries, so the logs may interleave the
+/// groups differently. The deviation of every floating point field present in
+/// both is reported. Lines that do not parse or decode are skipped, and each
+/// group is compared up to the end of the shorter log of that group.
+///
+/// Useful to confirm a firmware update did not change the telemetry scaling.
+pub fn compare_candump_logs(base_id: u16, before: &str, after: &str) -> Vec<Deviation> {
+    let before = replay(base_id, before);
+    let after = replay(base_id, after);
+
+    let mut deviations: Vec<Deviation> = FIELDS
+        .iter()
+        .map(|&field| Deviation {
+            field,
+            mean: 0.0,
+            max: 0.0,
+            samples: 0,
+        })
+        .collect();
+
+    let pairs = before
+        .iter()
+        .zip(&after)
+        .flat_map(|(before, after)| before.iter().zip(after));
+    for (before, after) in pairs {
+        let values = before.floats().into_iter().zip(after.floats());
+        for (deviation, values) in deviations.iter_mut().zip(values) {
+            if let (Some(before), Some(after)) = values {
+                let difference = after - before;
+                deviation.mean += difference;
+                deviation.max = deviation.max.max(difference.abs());
+                deviation.samples += 1;
+            }
+        }
+    }
+
+    deviations.retain(|d| d.samples > 0);
+    for deviation in &mut deviations {
+        deviation.mean /= deviation.samples as f32;
+    }
+
+    deviations
+}
+
+/// Decode a `candump` log into the fields carried by each frame, by group.
+fn replay(base_id: u16, log: &str) -> Vec<Vec<Status>> {
+    let mppt = Mppt::new(base_id);
+    let mut groups = vec![Vec::new(); BROADCAST_COUNT];
+
+    let frames = log
+        .lines()
+        .filter_map(parse_candump_line)
+        .filter_map(|(id, data, dlc)| {
+            let id = StandardId::new(id)?;
+            Some(Frame::new_data(id, Data::new(&data[..dlc])?))
+        });
+    for frame in frames {
+        if let Ok(Some((group, status))) = mppt.peek(&frame) {
+            groups[group as usize].push(status);
+        }
+    }
+
+    groups
+}
@@ -1,5 +1,7 @@
 #![allow(unused)]
 
+use core::ops::RangeInclusive;
+
 /// Default identifier with rotary switch in the 0 position.
 pub static ID_BASE: u16 = 0x600;
 
@@ -14,8 +16,57 @@ pub const ID_BROADCAST_AUX_POWER: u16 = 0x03;
 pub const ID_BROADCAST_LIMITS: u16 = 0x04;
 pub const ID_BROADCAST_STATUS: u16 = 0x05;
 pub const ID_BROADCAST_POWER_CONNECTOR: u16 = 0x06;
+/// Vendor specific extended status, not documented in the manual.
+#[cfg(feature = "vendor-extended-status")]
+pub const ID_BROADCAST_EXTENDED_STATUS: u16 = 0x07;
 
 // command message identifiers (normalized for base identifier offset)
 pub const ID_COMMAND_MODE: u16 = 0x08;
 pub const ID_COMMAND_MAX_OUTPUT_VOLTAGE: u16 = 0x0A;
 pub const ID_COMMAND_MAX_INPUT_CURERNT: u16 = 0x0B;
+
+// accepted setpoint ranges, rejecting negative and non-finite values
+pub const MAX_OUTPUT_VOLTAGE_RANGE: RangeInclusive<f32> = 0.0..=f32::MAX;
+pub const MAX_INPUT_CURRENT_RANGE: RangeInclusive<f32> = 0.0..=f32::MAX;
+
+// nominal operating envelope used by `Status::is_nominal`
+pub const NOMINAL_RAIL_12V: RangeInclusive<f32> = 10.8..=13.2;
+pub const NOMINAL_RAIL_3V: RangeInclusive<f32> = 2.97..=3.63;
+pub const NOMINAL_MOSFET_TEMPERATURE_MAX: f32 = 80.0;
+pub const NOMINAL_CONTROLLER_TEMPERATURE_MAX: f32 = 70.0;
+
+/// Largest expected difference between the two temperature sensors, used by
+/// `Status::validate`.
+pub const TEMPERATURE_SENSOR_MAX_DELTA: f32 = 20.0;
+
+/// MOSFET temperature at which `Mppt::thermal_derated_max_input` reaches zero.
+pub const DERATE_MOSFET_TEMPERATURE_LIMIT: f32 = 100.0;
+
+/// Output current below which `Status::output_disconnected` treats the output
+/// as carrying no current, in amps.
+pub const OUTPUT_CURRENT_ZERO: f32 = 0.01;
+
+/// Default time constant of `Mppt::time_weighted_input_power`, in seconds.
+pub const INPUT_POWER_TIME_CONSTANT: f32 = 60.0;
+
+/// Number of recent output broadcasts `Mppt::output_voltage_stddev` is
+/// weighted over.
+pub const OUTPUT_VOLTAGE_STDDEV_WINDOW: u32 = 64;
+
+/// Number of fault onsets kept by `Mppt::recent_faults`.
+pub const FAULT_HISTORY_LEN: usize = 8;
+
+/// Number of status frames of limit flags kept for
+/// `Mppt::limit_mode_oscillating`.
+pub const LIMIT_HISTORY_LEN: usize = 16;
+
+/// Input current at or below which `Status::at_minimum_input` accepts the
+/// minimum input current limit flag, in amps.
+pub const INPUT_CURRENT_MIN_THRESHOLD: f32 = 0.25;
+
+/// Largest forward step of the test counter across a wrap that is treated as
+/// lost status frames rather than a reboot.
+///
+/// Kept to a few frames, so a reboot from any counter value is only missed
+/// when it lands just past the wrap.
+pub const TEST_COUNTER_MAX_STEP: u8 = 4;
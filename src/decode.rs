@@ -0,0 +1,369 @@
+//! Table driven decoding of the broadcast messages and command echoes.
+//!
+//! Each handled message is an entry in [`MESSAGES`], and its payload is
+//! written into the status by [`decode`]. Supporting a new message requires
+//! adding its table entry and a decoder for its [`Broadcast`] variant, which
+//! the exhaustive match in [`decode`] enforces.
+
+use crate::*;
+
+/// Options controlling how payloads are decoded.
+#[derive(Format, Debug, Clone, Copy)]
+pub(crate) struct Decoding {
+    pub temperature_format: TemperatureFormat,
+    pub input_current_unit: CurrentUnit,
+    pub output_current_unit: CurrentUnit,
+    pub scaling: ScalingProfile,
+}
+
+impl Decoding {
+    /// Payload encodings as documented in the manual, without corrections.
+    pub const DOCUMENTED: Decoding = Decoding {
+        temperature_format: TemperatureFormat::Float,
+        input_current_unit: CurrentUnit::Amps,
+        output_current_unit: CurrentUnit::Amps,
+        scaling: ScalingProfile::identity(),
+    };
+}
+
+impl Status {
+    /// Status with every field `None`, for use in const contexts.
+    const EMPTY: Status = Status {
+        input_voltage: None,
+        input_current: None,
+        output_voltage: None,
+        output_current: None,
+        mostfet_temperature: None,
+        controller_temperature: None,
+        rail_12v: None,
+        rail_3v: None,
+        maximum_output_voltage: None,
+        maximum_input_current: None,
+        can_rx_error_count: None,
+        can_tx_error_count: None,
+        can_tx_overflow_count: None,
+        error_flags: None,
+        limit_flags: None,
+        raw_error_byte: None,
+        raw_limit_byte: None,
+        mode: None,
+        test_counter: None,
+        power_connector_voltage: None,
+        power_connector_temperature: None,
+        commanded_mode: None,
+        commanded_max_output_voltage: None,
+        commanded_max_input_current: None,
+        #[cfg(feature = "vendor-extended-status")]
+        uptime_s: None,
+        #[cfg(feature = "vendor-extended-status")]
+        lifetime_energy_wh: None,
+    };
+
+    /// Decode a full payload of the message at `offset` from the base
+    /// identifier into an empty status.
+    ///
+    /// Uses the encodings documented in the manual, like [`Status::apply`],
+    /// and can be evaluated in const contexts, for example to check known
+    /// payloads at compile time. Returns `None` for offsets of unhandled
+    /// messages.
+    pub const fn decode(offset: u16, data: &[u8; 8]) -> Option<Status> {
+        let message = match message(offset) {
+            Some(message) => message,
+            None => return None,
+        };
+
+        let mut status = Status::EMPTY;
+        decode(message.broadcast, &mut status, data, &Decoding::DOCUMENTED);
+        Some(status)
+    }
+}
+
+/// Broadcast message handled by the driver.
+pub(crate) struct Message {
+    /// Offset of the message from the base identifier.
+    pub offset: u16,
+    pub broadcast: Broadcast,
+    /// Minimum payload length needed to decode the message.
+    pub min_len: usize,
+    /// Whether the payload shows the device rebooted since `status`.
+    pub rebooted: fn(status: &Status, data: &[u8]) -> bool,
+}
+
+/// Every broadcast message handled by the driver, in the declaration order
+/// of [`Broadcast`].
+///
+/// Messages of two values need at least the first one; when the second is
+/// missing, the field it updates is left unchanged. The status message is
+/// always read in full. Command echoes carry the payload of the command and
+/// are not scaled.
+pub(crate) const MESSAGES: &[Message] = &[
+    Message {
+        offset: ID_BROADCAST_INPUT,
+        broadcast: Broadcast::Input,
+        min_len: 4,
+        rebooted: never,
+    },
+    Message {
+        offset: ID_BROADCAST_OUTPUT,
+        broadcast: Broadcast::Output,
+        min_len: 4,
+        rebooted: never,
+    },
+    Message {
+        offset: ID_BROADCAST_TEMPERATURE,
+        broadcast: Broadcast::Temperature,
+        min_len: 4,
+        rebooted: never,
+    },
+    Message {
+        offset: ID_BROADCAST_AUX_POWER,
+        broadcast: Broadcast::AuxPower,
+        min_len: 4,
+        rebooted: never,
+    },
+    Message {
+        offset: ID_BROADCAST_LIMITS,
+        broadcast: Broadcast::Limits,
+        min_len: 4,
+        rebooted: never,
+    },
+    Message {
+        offset: ID_BROADCAST_STATUS,
+        broadcast: Broadcast::StatusFrame,
+        min_len: 8,
+        rebooted: |status, data| {
+            // counting backwards means a reboot, unless frames were lost
+            // while the counter wrapped around
+            status.test_counter.is_some_and(|previous| {
+                data[7] < previous && data[7].wrapping_sub(previous) > TEST_COUNTER_MAX_STEP
+            })
+        },
+    },
+    Message {
+        offset: ID_BROADCAST_POWER_CONNECTOR,
+        broadcast: Broadcast::PowerConnector,
+        min_len: 4,
+        rebooted: never,
+    },
+    Message {
+        offset: ID_COMMAND_MODE,
+        broadcast: Broadcast::CommandMode,
+        min_len: 1,
+        rebooted: never,
+    },
+    Message {
+        offset: ID_COMMAND_MAX_OUTPUT_VOLTAGE,
+        broadcast: Broadcast::CommandMaxOutputVoltage,
+        min_len: 4,
+        rebooted: never,
+    },
+    Message {
+        offset: ID_COMMAND_MAX_INPUT_CURERNT,
+        broadcast: Broadcast::CommandMaxInputCurrent,
+        min_len: 4,
+        rebooted: never,
+    },
+    #[cfg(feature = "vendor-extended-status")]
+    Message {
+        offset: ID_BROADCAST_EXTENDED_STATUS,
+        broadcast: Broadcast::ExtendedStatus,
+        min_len: 4,
+        rebooted: |status, data| match (status.uptime_s, lower_u32(data)) {
+            (Some(previous), Some(uptime)) => uptime < previous,
+            _ => false,
+        },
+    },
+];
+
+// the telemetry group of each message indexes per group state
+const _: () = {
+    let mut i = 0;
+    while i < MESSAGES.len() {
+        assert!(
+            MESSAGES[i].broadcast as usize == i,
+            "MESSAGES must be in the declaration order of Broadcast"
+        );
+        i += 1;
+    }
+};
+
+/// Find the message at an offset from the base identifier.
+pub(crate) const fn message(offset: u16) -> Option<&'static Message> {
+    let mut i = 0;
+    while i < MESSAGES.len() {
+        if MESSAGES[i].offset == offset {
+            return Some(&MESSAGES[i]);
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Write the decoded payload of a message into the status.
+///
+/// This is the only decoder of the payloads, shared by [`Mppt::receive`],
+/// [`Mppt::peek`], [`Status::apply`] and the const [`Status::decode`].
+pub(crate) const fn decode(broadcast: Broadcast, status: &mut Status, data: &[u8], d: &Decoding) {
+    match broadcast {
+        Broadcast::Input => input(status, data, d),
+        Broadcast::Output => output(status, data, d),
+        Broadcast::Temperature => temperature(status, data, d),
+        Broadcast::AuxPower => aux_power(status, data, d),
+        Broadcast::Limits => limits(status, data, d),
+        Broadcast::StatusFrame => status_frame(status, data),
+        Broadcast::PowerConnector => power_connector(status, data, d),
+        Broadcast::CommandMode => command_mode(status, data),
+        Broadcast::CommandMaxOutputVoltage => command_max_output_voltage(status, data),
+        Broadcast::CommandMaxInputCurrent => command_max_input_current(status, data),
+        #[cfg(feature = "vendor-extended-status")]
+        Broadcast::ExtendedStatus => extended_status(status, data),
+    }
+}
+
+/// Write only the fields reboot detection compares against.
+///
+/// Used for frames that are otherwise discarded, so the next frame is checked
+/// against the latest counter rather than the last applied one.
+pub(crate) const fn track(broadcast: Broadcast, status: &mut Status, data: &[u8]) {
+    match broadcast {
+        Broadcast::StatusFrame => status.test_counter = Some(data[7]),
+        #[cfg(feature = "vendor-extended-status")]
+        Broadcast::ExtendedStatus => status.uptime_s = lower_u32(data),
+        _ => {}
+    }
+}
+
+const fn input(status: &mut Status, data: &[u8], d: &Decoding) {
+    status.input_voltage = d.scaling.input_voltage.correct(lower_float(data));
+    let current = upper_current(data, d.input_current_unit);
+    update(
+        &mut status.input_current,
+        d.scaling.input_current.correct(current),
+    );
+}
+
+const fn output(status: &mut Status, data: &[u8], d: &Decoding) {
+    status.output_voltage = d.scaling.output_voltage.correct(lower_float(data));
+    let current = upper_current(data, d.output_current_unit);
+    update(
+        &mut status.output_current,
+        d.scaling.output_current.correct(current),
+    );
+}
+
+const fn temperature(status: &mut Status, data: &[u8], d: &Decoding) {
+    let (mosfet, controller) = match d.temperature_format {
+        TemperatureFormat::Float => (lower_float(data), upper_float(data)),
+        TemperatureFormat::Centidegrees => (centidegrees(data, 0), centidegrees(data, 4)),
+    };
+    status.mostfet_temperature = d.scaling.mostfet_temperature.correct(mosfet);
+    update(
+        &mut status.controller_temperature,
+        d.scaling.controller_temperature.correct(controller),
+    );
+}
+
+const fn aux_power(status: &mut Status, data: &[u8], d: &Decoding) {
+    status.rail_12v = d.scaling.rail_12v.correct(lower_float(data));
+    update(
+        &mut status.rail_3v,
+        d.scaling.rail_3v.correct(upper_float(data)),
+    );
+}
+
+const fn limits(status: &mut Status, data: &[u8], d: &Decoding) {
+    status.maximum_output_voltage = d.scaling.maximum_output_voltage.correct(lower_float(data));
+    update(
+        &mut status.maximum_input_current,
+        d.scaling.maximum_input_current.correct(upper_float(data)),
+    );
+}
+
+const fn status_frame(status: &mut Status, data: &[u8]) {
+    status.can_rx_error_count = Some(data[0]);
+    status.can_tx_error_count = Some(data[1]);
+    status.can_tx_overflow_count = Some(data[2]);
+    status.error_flags = Some(ErrorFlags::from_bits_truncate(data[3]));
+    status.limit_flags = Some(LimitFlags::from_bits_truncate(data[4]));
+    status.raw_error_byte = Some(data[3]);
+    status.raw_limit_byte = Some(data[4]);
+    status.mode = Some(Mode::from_byte(data[5]));
+    status.test_counter = Some(data[7]);
+}
+
+const fn power_connector(status: &mut Status, data: &[u8], d: &Decoding) {
+    status.power_connector_voltage = d.scaling.power_connector_voltage.correct(lower_float(data));
+    update(
+        &mut status.power_connector_temperature,
+        d.scaling
+            .power_connector_temperature
+            .correct(upper_float(data)),
+    );
+}
+
+const fn command_mode(status: &mut Status, data: &[u8]) {
+    status.commanded_mode = Some(Mode::from_byte(data[0]));
+}
+
+const fn command_max_output_voltage(status: &mut Status, data: &[u8]) {
+    status.commanded_max_output_voltage = lower_float(data);
+}
+
+const fn command_max_input_current(status: &mut Status, data: &[u8]) {
+    status.commanded_max_input_current = lower_float(data);
+}
+
+#[cfg(feature = "vendor-extended-status")]
+const fn extended_status(status: &mut Status, data: &[u8]) {
+    status.uptime_s = lower_u32(data);
+    update(&mut status.lifetime_energy_wh, upper_float(data));
+}
+
+const fn update(field: &mut Option<f32>, value: Option<f32>) {
+    if value.is_some() {
+        *field = value;
+    }
+}
+
+fn never(_status: &Status, _data: &[u8]) -> bool {
+    false
+}
+
+const fn lower_float(data: &[u8]) -> Option<f32> {
+    match data {
+        [a, b, c, d, ..] => Some(f32::from_le_bytes([*a, *b, *c, *d])),
+        _ => None,
+    }
+}
+
+const fn upper_float(data: &[u8]) -> Option<f32> {
+    match data {
+        [_, _, _, _, a, b, c, d, ..] => Some(f32::from_le_bytes([*a, *b, *c, *d])),
+        _ => None,
+    }
+}
+
+const fn upper_current(data: &[u8], unit: CurrentUnit) -> Option<f32> {
+    match (unit, data) {
+        (CurrentUnit::Amps, _) => upper_float(data),
+        (CurrentUnit::Milliamps, [_, _, _, _, a, b, c, d, ..]) => {
+            Some(i32::from_le_bytes([*a, *b, *c, *d]) as f32 * 0.001)
+        }
+        (CurrentUnit::Milliamps, _) => None,
+    }
+}
+
+#[cfg(feature = "vendor-extended-status")]
+const fn lower_u32(data: &[u8]) -> Option<u32> {
+    match data {
+        [a, b, c, d, ..] => Some(u32::from_le_bytes([*a, *b, *c, *d])),
+        _ => None,
+    }
+}
+
+const fn centidegrees(data: &[u8], offset: usize) -> Option<f32> {
+    match data.len() >= offset + 2 {
+        true => Some(i16::from_le_bytes([data[offset], data[offset + 1]]) as f32 * 0.01),
+        false => None,
+    }
+}
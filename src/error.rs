@@ -0,0 +1,29 @@
+use defmt::Format;
+
+/// Errors returned by the driver.
+#[derive(Format, Debug, PartialEq, Clone, Copy)]
+pub enum Error {
+    /// The frame is a remote frame rather than a data frame.
+    NotADataFrame,
+    /// The identifier is within the device range but not a handled message.
+    ///
+    /// Contains the offset from the base identifier.
+    UnhandledMessageId(u16),
+    /// A base identifier of the other class (standard or extended) was given
+    /// to the device.
+    ///
+    /// Received frames of the other class are not an error, `receive` ignores
+    /// them like any frame that is not for the device.
+    WrongIdClass,
+    /// The payload is too short for the fields of the message.
+    ShortPayload { expected: usize, got: usize },
+    /// The base identifier leaves no room for the message identifiers of its
+    /// class.
+    InvalidBaseId(u32),
+    /// A commanded setpoint is outside of its accepted range.
+    OutOfRange { value: f32 },
+    /// The log record was written with an unsupported layout version.
+    UnsupportedLogVersion(u8),
+    /// The encoded status marks fields as present that do not exist.
+    MalformedStatus,
+}
@@ -0,0 +1,46 @@
+use crate::{Broadcast, Status};
+use defmt::Format;
+
+/// Smallest and largest value seen on a channel.
+#[derive(Format, Debug, Default, PartialEq, Clone, Copy)]
+pub struct Extreme {
+    pub min: Option<f32>,
+    pub max: Option<f32>,
+}
+
+impl Extreme {
+    fn record(&mut self, value: Option<f32>) {
+        let value = match value {
+            Some(value) if value.is_finite() => value,
+            _ => return,
+        };
+
+        self.min = Some(self.min.map_or(value, |min| min.min(value)));
+        self.max = Some(self.max.map_or(value, |max| max.max(value)));
+    }
+}
+
+/// Extremes of the voltage and temperature channels since the last reset.
+#[derive(Format, Debug, Default, PartialEq, Clone, Copy)]
+pub struct Extremes {
+    pub input_voltage: Extreme,
+    pub output_voltage: Extreme,
+    pub mostfet_temperature: Extreme,
+    pub controller_temperature: Extreme,
+}
+
+impl Extremes {
+    /// Record the channels carried by a decoded broadcast.
+    pub(crate) fn record(&mut self, broadcast: Broadcast, status: &Status) {
+        match broadcast {
+            Broadcast::Input => self.input_voltage.record(status.input_voltage),
+            Broadcast::Output => self.output_voltage.record(status.output_voltage),
+            Broadcast::Temperature => {
+                self.mostfet_temperature.record(status.mostfet_temperature);
+                self.controller_temperature
+                    .record(status.controller_temperature);
+            }
+            _ => {}
+        }
+    }
+}
@@ -30,3 +30,98 @@ bitflags! {
         const INPUT_CURRENT_MIN     = 1 << 7;
     }
 }
+
+bitflags! {
+    /// Problems found by `Status::validate`
+    #[derive(Format)]
+    pub struct ValidationIssues: u8 {
+        const NON_FINITE            = 1 << 0;
+        const OUT_OF_RANGE          = 1 << 1;
+        const INCONSISTENT_POWER    = 1 << 2;
+        const SENSOR_DISAGREEMENT   = 1 << 3;
+    }
+}
+
+/// Serialize flags as their raw bits, ignoring unknown bits when reading.
+#[cfg(feature = "serde")]
+macro_rules! serde_bits {
+    ($flags:ty) => {
+        impl serde::Serialize for $flags {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_u8(self.bits())
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $flags {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                <u8 as serde::Deserialize>::deserialize(deserializer).map(Self::from_bits_truncate)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "serde")]
+serde_bits!(ErrorFlags);
+#[cfg(feature = "serde")]
+serde_bits!(LimitFlags);
+
+impl ErrorFlags {
+    /// Iterate over each individual set flag.
+    pub fn active(&self) -> impl Iterator<Item = ErrorFlags> {
+        let flags = *self;
+        (0..8)
+            .map(|bit| ErrorFlags::from_bits_truncate(1 << bit))
+            .filter(move |flag| !flag.is_empty() && flags.contains(*flag))
+    }
+
+    /// Short description of a single flag.
+    pub fn describe(flag: ErrorFlags) -> &'static str {
+        match flag {
+            ErrorFlags::HW_OVERVOLTAGE => "hardware overvoltage",
+            ErrorFlags::HW_OVERCURRENT => "hardware overcurrent",
+            ErrorFlags::UNDERVOLTAGE_12V => "12V undervoltage",
+            ErrorFlags::BATTERY_FULL => "battery full",
+            ErrorFlags::BATTERY_LOW => "battery low",
+            ErrorFlags::MOSFET_OVERHEAT => "MOSFET overheated",
+            ErrorFlags::LOW_ARRAY_POWER => "low array power",
+            _ => "unknown",
+        }
+    }
+}
+
+impl LimitFlags {
+    /// Iterate over each individual set flag.
+    pub fn active(&self) -> impl Iterator<Item = LimitFlags> {
+        let flags = *self;
+        (0..8)
+            .map(|bit| LimitFlags::from_bits_truncate(1 << bit))
+            .filter(move |flag| flags.contains(*flag))
+    }
+
+    /// Short description of a single flag.
+    pub fn describe(flag: LimitFlags) -> &'static str {
+        match flag {
+            LimitFlags::GLOBAL_MPPT => "global MPPT",
+            LimitFlags::LOCAL_MPPT => "local MPPT",
+            LimitFlags::DUTY_CYCLE_MAX => "maximum duty cycle",
+            LimitFlags::DUTY_CYCLE_MIN => "minimum duty cycle",
+            LimitFlags::MOSFET_TEMPERATURE => "MOSFET temperature",
+            LimitFlags::OUTPUT_VOLTAGE_MAX => "maximum output voltage",
+            LimitFlags::INPUT_CURRENT_MAX => "maximum input current",
+            LimitFlags::INPUT_CURRENT_MIN => "minimum input current",
+            _ => "unknown",
+        }
+    }
+
+    /// Write each set flag into `out`, returning the filled part of the buffer.
+    pub fn active_list<'a>(&self, out: &'a mut [LimitFlags; 8]) -> &'a [LimitFlags] {
+        let mut count = 0;
+
+        for flag in self.active() {
+            out[count] = flag;
+            count += 1;
+        }
+
+        &out[..count]
+    }
+}
@@ -0,0 +1,36 @@
+use defmt::Format;
+
+/// The last `N` recorded values, dropping the oldest when full.
+#[derive(Format, Debug, Clone, Copy)]
+pub(crate) struct History<T, const N: usize> {
+    /// Recorded values, wrapping around at `next`.
+    values: [Option<T>; N],
+    /// Index the next value is written to.
+    next: usize,
+}
+
+impl<T: Copy, const N: usize> History<T, N> {
+    pub const fn new() -> Self {
+        Self {
+            values: [None; N],
+            next: 0,
+        }
+    }
+
+    /// Add a value, dropping the oldest if full.
+    pub fn record(&mut self, value: T) {
+        self.values[self.next] = Some(value);
+        self.next = (self.next + 1) % N;
+    }
+
+    /// Forget every recorded value.
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Recorded values, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = T> + '_ {
+        let (newer, older) = self.values.split_at(self.next);
+        older.iter().chain(newer).filter_map(|value| *value)
+    }
+}
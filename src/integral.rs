@@ -0,0 +1,80 @@
+use defmt::Format;
+
+/// Running integral of a value sampled at irregular times.
+///
+/// Each sample is held until the next one arrives, so a value is weighted by
+/// how long it was current. With a time constant, older time is exponentially
+/// discounted, so the mean follows recent values.
+#[derive(Format, Debug, Default, Clone, Copy)]
+pub(crate) struct Integral {
+    /// Time constant of the exponential discount in seconds, `None` to keep
+    /// everything.
+    time_constant: Option<f32>,
+    /// Previous sample and the time it was taken, in milliseconds.
+    sample: Option<(u32, f32)>,
+    /// Integral of the value over the held time, in value seconds, discounted
+    /// with the time constant.
+    pub total: f32,
+    /// Time covered by the integral, in seconds.
+    pub duration: f32,
+}
+
+impl Integral {
+    /// Integral discounting older time with a time constant of `seconds`.
+    ///
+    /// A time constant of zero or less keeps everything.
+    pub fn decaying(seconds: f32) -> Self {
+        Self {
+            time_constant: Some(seconds).filter(|t| *t > 0.0),
+            ..Self::default()
+        }
+    }
+
+    /// Add a sample taken at `now_ms`.
+    ///
+    /// An unknown value ends the held interval without adding to the integral.
+    pub fn record(&mut self, now_ms: u32, value: Option<f32>) {
+        if let Some((time, previous)) = self.sample {
+            let elapsed = now_ms.wrapping_sub(time) as f32 / 1000.0;
+            match self.time_constant {
+                Some(tau) => {
+                    // exact for a value held over the interval
+                    let decay = libm::expf(-elapsed / tau);
+                    let weight = tau * (1.0 - decay);
+                    self.total = self.total * decay + previous * weight;
+                    self.duration = self.duration * decay + weight;
+                }
+                None => {
+                    self.total += previous * elapsed;
+                    self.duration += elapsed;
+                }
+            }
+        }
+
+        self.sample = value.filter(|v| v.is_finite()).map(|v| (now_ms, v));
+    }
+
+    /// Forget the previous sample and the accumulated integral, keeping the
+    /// time constant.
+    pub fn clear(&mut self) {
+        *self = Self {
+            time_constant: self.time_constant,
+            ..Self::default()
+        };
+    }
+
+    /// Forget the previous sample, keeping the accumulated integral.
+    pub fn restart(&mut self) {
+        self.sample = None;
+    }
+
+    /// Time-weighted mean of the value, if any time has been covered.
+    ///
+    /// With a time constant this is the exponential moving average.
+    pub fn mean(&self) -> Option<f32> {
+        match self.duration > 0.0 {
+            true => Some(self.total / self.duration),
+            false => None,
+        }
+    }
+}
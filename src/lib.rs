@@ -3,28 +3,225 @@
 //! This a platform agnostic driver for the Elmar MPPT solar array power
 //! converter.
 
-#![no_std]
+#![cfg_attr(not(feature = "std"), no_std)]
 
+mod array;
+#[cfg(feature = "std")]
+mod candump;
 mod constants;
+mod decode;
+mod error;
+mod extremes;
 mod flags;
+mod history;
+mod integral;
+mod log;
+mod scaling;
+#[cfg(feature = "socketcan")]
+mod socketcan_interop;
+#[cfg(feature = "heapless")]
+mod summary;
+mod variance;
 
-use bxcan::{Frame, Id, StandardId};
+pub use array::*;
+use bxcan::{Data, ExtendedId, Frame, Id, StandardId, Tx};
+#[cfg(feature = "std")]
+pub use candump::*;
 pub use constants::*;
+use core::ops::RangeInclusive;
+use decode::Decoding;
 use defmt::Format;
+pub use error::*;
+pub use extremes::*;
 pub use flags::*;
+use history::History;
+use integral::Integral;
+pub use log::*;
+pub use scaling::*;
+#[cfg(feature = "socketcan")]
+pub use socketcan_interop::*;
+use variance::Variance;
 
 /// Operating mode.
-#[derive(Format, PartialEq, Clone, Copy)]
+#[derive(Format, Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Mode {
-    Standby = 0,
-    On = 1,
+    Standby,
+    On,
+    /// Mode value not known to this driver, reported by newer firmware.
+    Unknown(u8),
+}
+
+impl Mode {
+    /// Whether the mode is [`Mode::On`].
+    pub fn is_on(&self) -> bool {
+        *self == Mode::On
+    }
+
+    /// Whether the mode is [`Mode::Standby`].
+    pub fn is_standby(&self) -> bool {
+        *self == Mode::Standby
+    }
+
+    const fn from_byte(value: u8) -> Self {
+        match value {
+            0 => Mode::Standby,
+            1 => Mode::On,
+            v => Mode::Unknown(v),
+        }
+    }
+}
+
+impl From<u8> for Mode {
+    fn from(value: u8) -> Self {
+        Mode::from_byte(value)
+    }
+}
+
+impl From<Mode> for u8 {
+    fn from(mode: Mode) -> Self {
+        match mode {
+            Mode::Standby => 0,
+            Mode::On => 1,
+            Mode::Unknown(v) => v,
+        }
+    }
+}
+
+/// Command that can be sent to the device.
+#[derive(Format, Debug, PartialEq, Clone, Copy)]
+pub enum Command {
+    /// Set the operating mode.
+    Mode(Mode),
+    /// Set the maximum output voltage in volts.
+    MaxOutputVoltage(f32),
+    /// Set the maximum input current in amps.
+    MaxInputCurrent(f32),
+}
+
+/// Commanded mode and setpoints of a device, for restoring its
+/// configuration.
+///
+/// Setpoints are in volts and amps, before any [`CommandScaling`].
+#[derive(Format, Debug, PartialEq, Clone, Copy)]
+pub struct ConfigBlob {
+    pub mode: Mode,
+    pub max_output_voltage: f32,
+    pub max_input_current: f32,
+}
+
+/// Outcome of processing a frame with [`Mppt::observe`].
+#[derive(Format, Debug, PartialEq, Clone, Copy)]
+pub struct Observation {
+    /// Telemetry group that was updated, `None` if the frame was ignored.
+    pub group: Option<Broadcast>,
+    /// Whether the status changed since the last observation reporting a
+    /// change, ignoring the volatile counters (see
+    /// [`Status::eq_ignoring_counters`]) and float movements within the
+    /// deadband set with [`Mppt::with_change_deadband`].
+    pub changed: bool,
+    /// Error flags set by this frame that were not set before.
+    pub new_faults: ErrorFlags,
+    /// Time the group was previously received, `None` if this is the first
+    /// timestamped update of the group.
+    pub previous_update: Option<u32>,
+}
+
+/// Telemetry group carried by a broadcast message.
+///
+/// Each variant corresponds to one of the `ID_BROADCAST_*` identifiers, or
+/// to the echo of a command on one of the `ID_COMMAND_*` identifiers.
+#[derive(Format, Debug, PartialEq, Clone, Copy)]
+pub enum Broadcast {
+    Input,
+    Output,
+    Temperature,
+    AuxPower,
+    Limits,
+    StatusFrame,
+    PowerConnector,
+    CommandMode,
+    CommandMaxOutputVoltage,
+    CommandMaxInputCurrent,
+    #[cfg(feature = "vendor-extended-status")]
+    ExtendedStatus,
+}
+
+impl Broadcast {
+    /// Whether the group is the echo of a command rather than a broadcast by
+    /// the device.
+    pub fn is_command_echo(&self) -> bool {
+        matches!(
+            self,
+            Broadcast::CommandMode
+                | Broadcast::CommandMaxOutputVoltage
+                | Broadcast::CommandMaxInputCurrent
+        )
+    }
+}
+
+/// Number of [`Broadcast`] variants, one per decoded message.
+const BROADCAST_COUNT: usize = decode::MESSAGES.len();
+
+/// Receiver of status updates from [`Mppt::receive_into`].
+pub trait StatusSink {
+    /// Called after a telemetry group has been decoded, with the updated
+    /// status of the device.
+    fn on_update(&mut self, group: Broadcast, status: &Status);
+}
+
+/// Classification of the converter operating point.
+///
+/// See [`Status::operating_point`] for the classification rules.
+#[derive(Format, Debug, PartialEq, Clone, Copy)]
+pub enum OperatingPoint {
+    /// Converting power while tracking the maximum power point.
+    Harvesting,
+    /// No power is flowing to the output.
+    Idle,
+    /// Converting power but restricted by a limit other than MPPT tracking.
+    Limited,
+    /// One or more error flags are set.
+    Fault,
+}
+
+/// Encoding used by the device for a measured current.
+#[derive(Format, Debug, PartialEq, Clone, Copy, Default)]
+pub enum CurrentUnit {
+    /// Little-endian `f32` amps, as documented in the manual.
+    #[default]
+    Amps,
+    /// Little-endian two's-complement `i32` milliamps.
+    Milliamps,
+}
+
+/// Identifier class used by a device.
+#[derive(Format, Debug, PartialEq, Clone, Copy, Default)]
+pub enum IdKind {
+    /// 11-bit standard identifiers, as documented in the manual.
+    #[default]
+    Standard,
+    /// 29-bit extended identifiers.
+    Extended,
+}
+
+/// Encoding used by the device for the temperature broadcast.
+#[derive(Format, Debug, PartialEq, Clone, Copy, Default)]
+pub enum TemperatureFormat {
+    /// Little-endian `f32` degrees Celsius, as documented in the manual.
+    #[default]
+    Float,
+    /// Little-endian two's-complement `i16` in hundredths of a degree Celsius,
+    /// in the first two bytes of each half of the payload.
+    Centidegrees,
 }
 
 /// Status of the device.
 ///
 /// Each field is `Option<T>` as we do not know the value until a valid message
 /// is processed.
-#[derive(Format, Default, Clone, Copy)]
+#[derive(Format, Debug, Default, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Status {
     pub input_voltage: Option<f32>,
     pub input_current: Option<f32>,
@@ -41,97 +238,1277 @@ pub struct Status {
     pub can_tx_overflow_count: Option<u8>,
     pub error_flags: Option<ErrorFlags>,
     pub limit_flags: Option<LimitFlags>,
+    /// Error byte as received, including reserved bits.
+    pub raw_error_byte: Option<u8>,
+    /// Limit byte as received, including reserved bits.
+    pub raw_limit_byte: Option<u8>,
     pub mode: Option<Mode>,
     pub test_counter: Option<u8>,
     pub power_connector_voltage: Option<f32>,
     pub power_connector_temperature: Option<f32>,
+    /// Operating mode echoed by the device on the mode command identifier.
+    pub commanded_mode: Option<Mode>,
+    /// Maximum output voltage echoed by the device, as carried on the bus.
+    pub commanded_max_output_voltage: Option<f32>,
+    /// Maximum input current echoed by the device, as carried on the bus.
+    pub commanded_max_input_current: Option<f32>,
+    #[cfg(feature = "vendor-extended-status")]
+    pub uptime_s: Option<u32>,
+    #[cfg(feature = "vendor-extended-status")]
+    pub lifetime_energy_wh: Option<f32>,
 }
 
+impl Status {
+    /// Invalidate every value, setting all fields back to `None`.
+    pub fn clear(&mut self) {
+        *self = Status::default();
+    }
+
+    /// Decode the payload of an already classified message into the status.
+    ///
+    /// Uses the encodings documented in the manual, without the corrections
+    /// or reboot detection of an [`Mppt`]. A full payload is always long
+    /// enough, so this does not currently fail.
+    pub fn apply(&mut self, kind: Broadcast, data: &[u8; 8]) -> Result<(), Error> {
+        decode::decode(kind, self, data, &Decoding::DOCUMENTED);
+        Ok(())
+    }
+
+    /// Compare two statuses ignoring the volatile counters.
+    ///
+    /// The CAN error counts, test counter and (if enabled) vendor uptime are
+    /// not compared, so this reports whether the physical telemetry, flags or
+    /// mode changed.
+    pub fn eq_ignoring_counters(&self, other: &Status) -> bool {
+        self.without_counters() == other.without_counters()
+    }
+
+    fn floats(&self) -> [Option<f32>; 14] {
+        [
+            self.input_voltage,
+            self.input_current,
+            self.output_voltage,
+            self.output_current,
+            self.mostfet_temperature,
+            self.controller_temperature,
+            self.rail_12v,
+            self.rail_3v,
+            self.maximum_output_voltage,
+            self.maximum_input_current,
+            self.power_connector_voltage,
+            self.power_connector_temperature,
+            self.commanded_max_output_voltage,
+            self.commanded_max_input_current,
+        ]
+    }
+
+    /// Compare ignoring the volatile counters, treating float fields within
+    /// `deadband` of each other as equal.
+    fn eq_within(&self, other: &Status, deadband: f32) -> bool {
+        let floats_close = self
+            .floats()
+            .iter()
+            .zip(other.floats())
+            .all(|(a, b)| match (a, b) {
+                (Some(a), Some(b)) => *a == b || libm::fabsf(a - b) <= deadband,
+                (a, b) => a.is_none() && b.is_none(),
+            });
+
+        floats_close
+            && self
+                .without_floats()
+                .eq_ignoring_counters(&other.without_floats())
+    }
+
+    fn without_floats(&self) -> Status {
+        Status {
+            input_voltage: None,
+            input_current: None,
+            output_voltage: None,
+            output_current: None,
+            mostfet_temperature: None,
+            controller_temperature: None,
+            rail_12v: None,
+            rail_3v: None,
+            maximum_output_voltage: None,
+            maximum_input_current: None,
+            power_connector_voltage: None,
+            power_connector_temperature: None,
+            commanded_max_output_voltage: None,
+            commanded_max_input_current: None,
+            ..*self
+        }
+    }
+
+    fn without_counters(&self) -> Status {
+        Status {
+            can_rx_error_count: None,
+            can_tx_error_count: None,
+            can_tx_overflow_count: None,
+            test_counter: None,
+            #[cfg(feature = "vendor-extended-status")]
+            uptime_s: None,
+            ..*self
+        }
+    }
+
+    /// Error and limit flags packed into a byte each, for compact fault
+    /// reporting.
+    ///
+    /// Returns `None` until the status message has been received.
+    pub fn fault_bytes(&self) -> Option<(u8, u8)> {
+        Some((self.error_flags?.bits(), self.limit_flags?.bits()))
+    }
+
+    /// Rebuild a status holding only the flags from [`Status::fault_bytes`].
+    pub fn from_fault_bytes((error, limit): (u8, u8)) -> Status {
+        Status {
+            error_flags: Some(ErrorFlags::from_bits_truncate(error)),
+            limit_flags: Some(LimitFlags::from_bits_truncate(limit)),
+            raw_error_byte: Some(error),
+            raw_limit_byte: Some(limit),
+            ..Default::default()
+        }
+    }
+
+    /// Input power in watts, if the input voltage and current are known.
+    pub fn input_power(&self) -> Option<f32> {
+        product(self.input_voltage, self.input_current)
+    }
+
+    /// Output power in watts, if the output voltage and current are known.
+    pub fn output_power(&self) -> Option<f32> {
+        product(self.output_voltage, self.output_current)
+    }
+
+    /// Battery charge rate in C for a pack of `capacity_ah` amp hours.
+    ///
+    /// Returns `None` if the output current is unknown or the capacity is not
+    /// positive.
+    pub fn charge_rate_c(&self, capacity_ah: f32) -> Option<f32> {
+        let current = self.output_current?;
+
+        if capacity_ah <= 0.0 {
+            return None;
+        }
+
+        Some(current / capacity_ah)
+    }
+
+    /// Conversion efficiency as a fraction of input power.
+    ///
+    /// Returns `None` if either power is unknown or the input power is not
+    /// positive.
+    pub fn efficiency(&self) -> Option<f32> {
+        let input = self.input_power()?;
+        let output = self.output_power()?;
+
+        if input <= 0.0 {
+            return None;
+        }
+
+        Some(output / input)
+    }
+
+    /// Check the status for problems, returning every issue found.
+    ///
+    /// - [`ValidationIssues::NON_FINITE`]: a measurement is NaN or infinite,
+    /// - [`ValidationIssues::OUT_OF_RANGE`]: a voltage is negative, a rail is
+    ///   outside its nominal range or a temperature is above its nominal
+    ///   maximum,
+    /// - [`ValidationIssues::INCONSISTENT_POWER`]: output power exceeds input
+    ///   power,
+    /// - [`ValidationIssues::SENSOR_DISAGREEMENT`]: the temperature sensors
+    ///   differ by more than [`TEMPERATURE_SENSOR_MAX_DELTA`].
+    ///
+    /// Fields that have not been received are not checked.
+    pub fn validate(&self) -> ValidationIssues {
+        let mut issues = ValidationIssues::empty();
+
+        if self.floats().iter().flatten().any(|v| !v.is_finite()) {
+            issues |= ValidationIssues::NON_FINITE;
+        }
+
+        let negative = |v: Option<f32>| v.is_some_and(|v| v < 0.0);
+        let outside =
+            |v: Option<f32>, range: &RangeInclusive<f32>| v.is_some_and(|v| !range.contains(&v));
+        let above = |v: Option<f32>, max: f32| v.is_some_and(|v| v > max);
+
+        if negative(self.input_voltage)
+            || negative(self.output_voltage)
+            || negative(self.power_connector_voltage)
+            || outside(self.rail_12v, &NOMINAL_RAIL_12V)
+            || outside(self.rail_3v, &NOMINAL_RAIL_3V)
+            || above(self.mostfet_temperature, NOMINAL_MOSFET_TEMPERATURE_MAX)
+            || above(
+                self.controller_temperature,
+                NOMINAL_CONTROLLER_TEMPERATURE_MAX,
+            )
+        {
+            issues |= ValidationIssues::OUT_OF_RANGE;
+        }
+
+        if let (Some(input), Some(output)) = (self.input_power(), self.output_power()) {
+            if output > input {
+                issues |= ValidationIssues::INCONSISTENT_POWER;
+            }
+        }
+
+        if self.temperature_sensors_agree(TEMPERATURE_SENSOR_MAX_DELTA) == Some(false) {
+            issues |= ValidationIssues::SENSOR_DISAGREEMENT;
+        }
+
+        issues
+    }
+
+    /// Whether every monitored value is within the normal operating envelope.
+    ///
+    /// The following conditions are checked:
+    ///
+    /// - no error flags are set,
+    /// - the 12 V and 3 V rails are within [`NOMINAL_RAIL_12V`] and
+    ///   [`NOMINAL_RAIL_3V`],
+    /// - the MOSFET and controller temperatures are below
+    ///   [`NOMINAL_MOSFET_TEMPERATURE_MAX`] and
+    ///   [`NOMINAL_CONTROLLER_TEMPERATURE_MAX`],
+    /// - the conversion efficiency does not exceed 100 % (only checked when
+    ///   input and output power are known and input power is positive).
+    ///
+    /// Returns `None` if the error flags, rails or temperatures have not been
+    /// received yet.
+    pub fn is_nominal(&self) -> Option<bool> {
+        let error_flags = self.error_flags?;
+        let (rail_12v, rail_3v) = (self.rail_12v?, self.rail_3v?);
+        let (mosfet, controller) = (self.mostfet_temperature?, self.controller_temperature?);
+
+        let no_errors = error_flags.is_empty();
+        let rails = NOMINAL_RAIL_12V.contains(&rail_12v) && NOMINAL_RAIL_3V.contains(&rail_3v);
+        let temperatures = mosfet < NOMINAL_MOSFET_TEMPERATURE_MAX
+            && controller < NOMINAL_CONTROLLER_TEMPERATURE_MAX;
+
+        let efficiency = self.efficiency().is_none_or(|e| e <= 1.0);
+
+        Some(no_errors && rails && temperatures && efficiency)
+    }
+
+    /// Whether the input current is pinned at the device minimum.
+    ///
+    /// This is true when the [`LimitFlags::INPUT_CURRENT_MIN`] flag is set and
+    /// the measured input current is at most [`INPUT_CURRENT_MIN_THRESHOLD`],
+    /// which usually means there is effectively no power to harvest. A flag
+    /// contradicted by a higher current (for example while the status frame
+    /// lags a rising input) is not trusted. Returns `None` until
+    /// both the limit flags and input current have been received.
+    pub fn at_minimum_input(&self) -> Option<bool> {
+        let flagged = self.limit_flags?.contains(LimitFlags::INPUT_CURRENT_MIN);
+        let current = self.input_current?;
+
+        Some(flagged && libm::fabsf(current) <= INPUT_CURRENT_MIN_THRESHOLD)
+    }
+
+    /// Whether the output looks disconnected while power is harvested.
+    ///
+    /// True when the input power exceeds `min_input_power` watts but the
+    /// output current is below [`OUTPUT_CURRENT_ZERO`], which may mean the
+    /// battery is disconnected. Returns `None` if the input power or output
+    /// current is unknown.
+    pub fn output_disconnected(&self, min_input_power: f32) -> Option<bool> {
+        let input = self.input_power()?;
+        let current = self.output_current?;
+
+        Some(input > min_input_power && libm::fabsf(current) < OUTPUT_CURRENT_ZERO)
+    }
+
+    /// Power dissipated in the converter in watts.
+    ///
+    /// Calculated as input power minus output power. A negative result is not
+    /// physically possible and indicates a decode error, so `None` is
+    /// returned rather than a clamped value. Also `None` if any voltage or
+    /// current is missing.
+    pub fn power_loss(&self) -> Option<f32> {
+        let input = self.input_power()?;
+        let output = self.output_power()?;
+
+        match input - output {
+            loss if loss >= 0.0 => Some(loss),
+            _ => None,
+        }
+    }
+
+    /// Estimate the fill factor of the array at the current operating point.
+    ///
+    /// Calculated as `(input_voltage * input_current) / (voc * isc)` from the
+    /// open-circuit voltage and short-circuit current of the array. Returns
+    /// `None` if the input power is unknown or either reference is not
+    /// positive.
+    pub fn estimated_fill_factor(&self, voc: f32, isc: f32) -> Option<f32> {
+        let power = self.input_power()?;
+
+        if voc <= 0.0 || isc <= 0.0 {
+            return None;
+        }
+
+        Some(power / (voc * isc))
+    }
+
+    /// Classify the current operating point of the converter.
+    ///
+    /// The first matching rule applies:
+    ///
+    /// 1. [`OperatingPoint::Fault`] if any error flag is set,
+    /// 2. [`OperatingPoint::Idle`] if output power is not positive,
+    /// 3. [`OperatingPoint::Limited`] if any limit flag other than
+    ///    [`LimitFlags::GLOBAL_MPPT`] or [`LimitFlags::LOCAL_MPPT`] is set,
+    /// 4. [`OperatingPoint::Harvesting`] otherwise.
+    ///
+    /// Returns `None` until the flags, output voltage and output current have
+    /// been received.
+    pub fn operating_point(&self) -> Option<OperatingPoint> {
+        let error_flags = self.error_flags?;
+        let limit_flags = self.limit_flags?;
+        let output_power = self.output_power()?;
+        let tracking = LimitFlags::GLOBAL_MPPT | LimitFlags::LOCAL_MPPT;
+
+        Some(if !error_flags.is_empty() {
+            OperatingPoint::Fault
+        } else if output_power <= 0.0 {
+            OperatingPoint::Idle
+        } else if !(limit_flags - tracking).is_empty() {
+            OperatingPoint::Limited
+        } else {
+            OperatingPoint::Harvesting
+        })
+    }
+
+    /// Whether the MOSFET and controller temperature sensors agree.
+    ///
+    /// Returns `Some(false)` when the readings differ by more than
+    /// `max_delta` degrees Celsius, which may indicate a sensor fault, or
+    /// `None` if either temperature is unknown.
+    pub fn temperature_sensors_agree(&self, max_delta: f32) -> Option<bool> {
+        let mosfet = self.mostfet_temperature?;
+        let controller = self.controller_temperature?;
+
+        Some(libm::fabsf(mosfet - controller) <= max_delta)
+    }
+
+    /// Estimate the resistance of the power connector in ohms.
+    ///
+    /// Assumes the connector heats above `ambient` by `thermal_coeff` degrees
+    /// Celsius per watt dissipated, with the output current flowing through
+    /// it, so `R = (power_connector_temperature - ambient) / (thermal_coeff *
+    /// output_current^2)`. Returns `None` if either value is unknown, no
+    /// current flows, the coefficient is not positive or the connector is
+    /// below ambient.
+    pub fn connector_resistance_estimate(&self, ambient: f32, thermal_coeff: f32) -> Option<f32> {
+        let rise = self.power_connector_temperature? - ambient;
+        let current = self.output_current?;
+        let dissipation = thermal_coeff * current * current;
+
+        if rise < 0.0 || dissipation <= 0.0 {
+            return None;
+        }
+
+        Some(rise / dissipation)
+    }
+
+    /// Whether the 12 V control rail has browned out.
+    ///
+    /// This is driven by the [`ErrorFlags::UNDERVOLTAGE_12V`] flag. When the
+    /// 12 V rail measurement is also known, the flag is only considered
+    /// confirmed if the rail is below [`NOMINAL_RAIL_12V`]. Returns `None`
+    /// until the error flags have been received.
+    pub fn control_rail_brownout(&self) -> Option<bool> {
+        let flagged = self.error_flags?.contains(ErrorFlags::UNDERVOLTAGE_12V);
+
+        Some(match self.rail_12v {
+            Some(rail) => flagged && rail < *NOMINAL_RAIL_12V.start(),
+            None => flagged,
+        })
+    }
+}
+
+/// Limit flags tracked by [`Mppt::limit_mode_oscillating`].
+const LIMIT_MODES: LimitFlags = LimitFlags::INPUT_CURRENT_MAX.union(LimitFlags::OUTPUT_VOLTAGE_MAX);
+
 /// MPPT device.
-#[derive(Clone, Copy)]
+#[derive(Format, Debug, Clone, Copy)]
 pub struct Mppt {
+    /// Human readable label used in logs.
+    name: Option<&'static str>,
+
     /// Base identifier used by the MPPT.
     ///
     /// See the manual for configuring the identifier.
-    base_id: u16,
+    base_id: u32,
+
+    /// Identifier class of the received and sent frames.
+    id_kind: IdKind,
+
+    /// Number of bytes to skip at the start of each received payload.
+    payload_offset: usize,
+
+    /// Options controlling how payloads are decoded.
+    decoding: Decoding,
+
+    /// Conversions applied to commanded setpoints.
+    command_scaling: CommandScaling,
+
+    /// Last mode commanded through this instance.
+    commanded_mode: Option<Mode>,
+
+    /// Last maximum output voltage commanded through this instance, in volts.
+    commanded_max_output_voltage: Option<f32>,
+
+    /// Last maximum input current commanded through this instance, in amps.
+    commanded_max_input_current: Option<f32>,
+
+    /// Set when a counter reset indicating a device reboot is observed.
+    reboot_detected: bool,
+
+    /// Clear the status when a reboot is detected.
+    reset_on_reboot: bool,
+
+    /// Time of the last update of each telemetry group, in caller supplied
+    /// milliseconds.
+    last_update: [Option<u32>; BROADCAST_COUNT],
+
+    /// Minimum time between applied updates of a telemetry group, in
+    /// milliseconds.
+    min_status_interval: Option<u32>,
+
+    /// Time each telemetry group was last applied to the status.
+    last_applied: [Option<u32>; BROADCAST_COUNT],
+
+    /// Input power integrated over the timestamped input broadcasts, forgetting
+    /// old readings.
+    input_power: Integral,
+
+    /// Output power integrated over the timestamped output broadcasts.
+    output_power: Integral,
+
+    /// Last timestamped MOSFET temperature and the time it was received.
+    mosfet_sample: Option<(u32, f32)>,
+
+    /// Rate of change of the MOSFET temperature between the last two
+    /// samples, in degrees Celsius per second.
+    mosfet_rate: Option<f32>,
+
+    /// Track channel extremes as broadcasts are received.
+    track_extremes: bool,
+
+    /// Channel extremes since the last reset.
+    extremes: Extremes,
+
+    /// Variance of the output voltage broadcasts.
+    output_voltage_variance: Variance,
+
+    /// Timestamped onsets of device faults, oldest first.
+    recent_faults: History<(u32, ErrorFlags), FAULT_HISTORY_LEN>,
+
+    /// Input current and output voltage limit flags of the recent status
+    /// frames, oldest first.
+    limit_modes: History<LimitFlags, LIMIT_HISTORY_LEN>,
+
+    /// Float movement ignored when detecting changes.
+    change_deadband: f32,
+
+    /// Status when [`Mppt::observe`] last reported a change.
+    observed: Status,
 
     status: Status,
 }
 
 impl Mppt {
-    /// Create a new MPPT instance.
+    /// Create a new MPPT instance using standard identifiers.
+    ///
+    /// The base identifier must leave room for the command identifiers below
+    /// `0x800`, otherwise building a command frame panics. See
+    /// [`Mppt::set_base_id`] for a checked alternative.
     pub fn new(base_id: u16) -> Self {
         Self {
-            base_id,
+            name: None,
+            base_id: base_id as u32,
+            id_kind: IdKind::Standard,
+            payload_offset: 0,
+            decoding: Decoding::DOCUMENTED,
+            command_scaling: CommandScaling::identity(),
+            commanded_mode: None,
+            commanded_max_output_voltage: None,
+            commanded_max_input_current: None,
+            reboot_detected: false,
+            reset_on_reboot: false,
+            last_update: [None; BROADCAST_COUNT],
+            min_status_interval: None,
+            last_applied: [None; BROADCAST_COUNT],
+            input_power: Integral::decaying(INPUT_POWER_TIME_CONSTANT),
+            output_power: Integral::default(),
+            mosfet_sample: None,
+            mosfet_rate: None,
+            track_extremes: false,
+            extremes: Extremes::default(),
+            output_voltage_variance: Variance::windowed(OUTPUT_VOLTAGE_STDDEV_WINDOW),
+            recent_faults: History::new(),
+            limit_modes: History::new(),
+            change_deadband: 0.0,
+            observed: Status::default(),
             status: Status {
                 ..Default::default()
             },
         }
     }
 
+    /// Create a new MPPT instance from the rotary switch position.
+    ///
+    /// Returns `None` if the position is not between 0 and 15.
+    pub fn from_switch(position: u8) -> Option<Self> {
+        match position {
+            0..=15 => Some(Self::new(ID_BASE + ID_INC * position as u16)),
+            _ => None,
+        }
+    }
+
+    /// Create a new MPPT instance using extended identifiers.
+    ///
+    /// Messages are at the same offsets from the base identifier as with
+    /// standard identifiers. Returns `None` if the base identifier leaves no
+    /// room for the command identifiers below `0x2000_0000`.
+    pub fn new_extended(base_id: u32) -> Option<Self> {
+        let mut mppt = Self {
+            id_kind: IdKind::Extended,
+            ..Self::new(0)
+        };
+
+        mppt.set_extended_base_id(base_id).ok()?;
+        Some(mppt)
+    }
+
+    /// Get the base identifier of a device on standard identifiers.
+    ///
+    /// For a device on extended identifiers this is the identifier truncated
+    /// to 16 bits, use [`Mppt::extended_base_id`] instead.
+    pub fn base_id(&self) -> u16 {
+        self.base_id as u16
+    }
+
+    /// Get the base identifier of the device as a raw value of either class.
+    pub fn extended_base_id(&self) -> u32 {
+        self.base_id
+    }
+
+    /// Get the identifier class used by the device.
+    pub fn id_kind(&self) -> IdKind {
+        self.id_kind
+    }
+
+    /// Change the base identifier of a device on standard identifiers,
+    /// keeping the current status.
+    ///
+    /// Returns [`Error::WrongIdClass`] for a device on extended identifiers,
+    /// and [`Error::InvalidBaseId`] if the identifier leaves no room for the
+    /// command identifiers.
+    pub fn set_base_id(&mut self, id: u16) -> Result<(), Error> {
+        self.change_base_id(IdKind::Standard, id as u32)
+    }
+
+    /// Change the base identifier of a device on extended identifiers,
+    /// keeping the current status.
+    ///
+    /// Errors as for [`Mppt::set_base_id`].
+    pub fn set_extended_base_id(&mut self, id: u32) -> Result<(), Error> {
+        self.change_base_id(IdKind::Extended, id)
+    }
+
+    /// Follow a device on standard identifiers that moved to a new base
+    /// identifier.
+    ///
+    /// Unlike [`Mppt::set_base_id`] the status, accumulated energy and power
+    /// average, extremes, fault history and commanded setpoints are cleared,
+    /// as values received at the old identifier may not belong to the device. Errors as for
+    /// [`Mppt::set_base_id`], in which case nothing changes.
+    pub fn follow(&mut self, new_base_id: u16) -> Result<(), Error> {
+        self.set_base_id(new_base_id)?;
+        self.forget_device();
+        Ok(())
+    }
+
+    /// Follow a device on extended identifiers that moved to a new base
+    /// identifier.
+    ///
+    /// See [`Mppt::follow`].
+    pub fn follow_extended(&mut self, new_base_id: u32) -> Result<(), Error> {
+        self.set_extended_base_id(new_base_id)?;
+        self.forget_device();
+        Ok(())
+    }
+
+    /// Forget everything learned from the previously followed device.
+    fn forget_device(&mut self) {
+        self.reboot_detected = false;
+        self.reset_status();
+        self.input_power.clear();
+        self.output_power.clear();
+        self.extremes = Extremes::default();
+        self.recent_faults.clear();
+        self.observed = Status::default();
+        self.commanded_mode = None;
+        self.commanded_max_output_voltage = None;
+        self.commanded_max_input_current = None;
+    }
+
+    fn change_base_id(&mut self, kind: IdKind, id: u32) -> Result<(), Error> {
+        let max = match kind {
+            IdKind::Standard => StandardId::MAX.as_raw() as u32,
+            IdKind::Extended => ExtendedId::MAX.as_raw(),
+        };
+
+        if kind != self.id_kind {
+            return Err(Error::WrongIdClass);
+        }
+
+        if id > max - ID_COMMAND_MAX_INPUT_CURERNT as u32 {
+            return Err(Error::InvalidBaseId(id));
+        }
+
+        self.base_id = id;
+        Ok(())
+    }
+
+    /// Invalidate all received telemetry, keeping the configuration.
+    ///
+    /// Every status field reads `None` and every group is stale until new
+    /// messages arrive. The output voltage deviation starts over.
+    pub fn reset_status(&mut self) {
+        self.status.clear();
+        self.last_update = [None; BROADCAST_COUNT];
+        self.last_applied = [None; BROADCAST_COUNT];
+        self.input_power.restart();
+        self.output_power.restart();
+        self.mosfet_sample = None;
+        self.mosfet_rate = None;
+        self.output_voltage_variance.clear();
+        self.limit_modes.clear();
+    }
+
+    /// Set a human readable label for the device, included in `defmt` output.
+    pub fn with_name(mut self, name: &'static str) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    /// Get the label of the device, if one was set.
+    pub fn name(&self) -> Option<&'static str> {
+        self.name
+    }
+
+    /// Clear the status when a device reboot is detected.
+    ///
+    /// The status is cleared before the frame revealing the reboot is
+    /// applied, so values from before the reboot do not linger. Off by
+    /// default.
+    pub fn with_reset_on_reboot(mut self, reset: bool) -> Self {
+        self.reset_on_reboot = reset;
+        self
+    }
+
+    /// Track the extremes of the voltage and temperature channels.
+    ///
+    /// Off by default. See [`Mppt::extremes`].
+    pub fn with_extremes(mut self, track: bool) -> Self {
+        self.track_extremes = track;
+        self
+    }
+
+    /// Set the encoding used by the device for the temperature broadcast.
+    ///
+    /// Firmware using integer temperatures should be configured with
+    /// [`TemperatureFormat::Centidegrees`].
+    pub fn with_temperature_format(mut self, format: TemperatureFormat) -> Self {
+        self.decoding.temperature_format = format;
+        self
+    }
+
+    /// Set the encoding used by the device for the input current.
+    ///
+    /// The stored value is always in amps.
+    pub fn with_input_current_unit(mut self, unit: CurrentUnit) -> Self {
+        self.decoding.input_current_unit = unit;
+        self
+    }
+
+    /// Set the encoding used by the device for the output current.
+    ///
+    /// The stored value is always in amps.
+    pub fn with_output_current_unit(mut self, unit: CurrentUnit) -> Self {
+        self.decoding.output_current_unit = unit;
+        self
+    }
+
+    /// Skip `offset` bytes at the start of each received payload.
+    ///
+    /// This is a workaround for misconfigured gateways that prepend data (such
+    /// as a length byte) to the CAN payload. Fields that no longer fit in the
+    /// remaining bytes are left unchanged.
+    pub fn with_payload_offset(mut self, offset: usize) -> Self {
+        self.payload_offset = offset;
+        self
+    }
+
+    /// Set the corrections applied to measurements after they are decoded.
+    pub fn with_scaling_profile(mut self, scaling: ScalingProfile) -> Self {
+        self.decoding.scaling = scaling;
+        self
+    }
+
+    /// Apply each telemetry group at most once per `interval_ms` in
+    /// [`Mppt::receive_at`].
+    ///
+    /// Frames arriving sooner are discarded, but still count as received for
+    /// [`Mppt::is_stale`] and still update the test counter for
+    /// [`Mppt::detected_reboot`]. Useful to decouple a slow consumer from the
+    /// broadcast rate. Frames processed with [`Mppt::receive`] are not
+    /// throttled.
+    pub fn with_min_status_interval(mut self, interval_ms: u32) -> Self {
+        self.min_status_interval = Some(interval_ms);
+        self
+    }
+
+    /// Only report a float field as changed in [`Observation::changed`] once
+    /// it moves by more than `eps` from the value last reported.
+    ///
+    /// Hides measurement jitter from change based telemetry, while a slow
+    /// drift is still reported once it exceeds `eps`. Defaults to zero, so
+    /// any movement is a change.
+    pub fn with_change_deadband(mut self, eps: f32) -> Self {
+        self.change_deadband = eps;
+        self
+    }
+
+    /// Set the time constant of [`Mppt::time_weighted_input_power`] in
+    /// seconds.
+    ///
+    /// Readings older than a few time constants no longer contribute. Defaults
+    /// to [`INPUT_POWER_TIME_CONSTANT`]; zero or less averages over the whole
+    /// session.
+    pub fn with_input_power_time_constant(mut self, seconds: f32) -> Self {
+        self.input_power = Integral::decaying(seconds);
+        self
+    }
+
+    /// Set the conversions applied to setpoints before they are commanded.
+    pub fn with_command_scaling(mut self, scaling: CommandScaling) -> Self {
+        self.command_scaling = scaling;
+        self
+    }
+
     /// Process an incoming message and update the device status as needed.
     ///
-    /// If the message identifier does not match the device, this returns `Ok`.
-    pub fn receive(&mut self, frame: &Frame) -> Result<(), &'static str> {
-        match frame.id() {
-            Id::Standard(id) => {
-                if !frame.is_data_frame() {
-                    return Err("frame is not a data frame");
-                }
-
-                let data = frame.data().expect("msg has data");
-
-                if id.as_raw() >= self.base_id {
-                    match id.as_raw() - self.base_id {
-                        ID_BROADCAST_INPUT => {
-                            self.status.input_voltage = lower_float(data);
-                            self.status.input_current = upper_float(data);
-                        }
-                        ID_BROADCAST_OUTPUT => {
-                            self.status.output_voltage = lower_float(data);
-                            self.status.output_current = upper_float(data);
-                        }
-                        ID_BROADCAST_TEMPERATURE => {
-                            self.status.mostfet_temperature = lower_float(data);
-                            self.status.controller_temperature = upper_float(data);
-                        }
-                        ID_BROADCAST_AUX_POWER => {
-                            self.status.rail_12v = lower_float(data);
-                            self.status.rail_3v = upper_float(data);
-                        }
-                        ID_BROADCAST_LIMITS => {
-                            self.status.maximum_output_voltage = lower_float(data);
-                            self.status.maximum_input_current = upper_float(data);
-                        }
-                        ID_BROADCAST_STATUS => {
-                            self.status.can_rx_error_count = Some(data[0]);
-                            self.status.can_tx_error_count = Some(data[1]);
-                            self.status.can_tx_overflow_count = Some(data[2]);
-                            self.status.error_flags = Some(ErrorFlags::from_bits_truncate(data[3]));
-                            self.status.limit_flags = Some(LimitFlags::from_bits_truncate(data[4]));
-                            self.status.mode = match data[5] {
-                                0 => Some(Mode::Standby),
-                                1 => Some(Mode::On),
-                                _ => return Err("mode value read was invalid"),
-                            };
-                            self.status.test_counter = Some(data[7]);
-                        }
-                        ID_BROADCAST_POWER_CONNECTOR => {
-                            self.status.power_connector_voltage = lower_float(data);
-                            self.status.power_connector_temperature = upper_float(data);
-                        }
-                        _ => {
-                            return Err("message id not handled");
-                        }
-                    }
-                }
+    /// Returns the telemetry group that was updated, or `None` if the frame
+    /// is not for this device (see [`Mppt::is_for_device`]).
+    pub fn receive(&mut self, frame: &Frame) -> Result<Option<Broadcast>, Error> {
+        if !self.is_for_device(frame) {
+            return Ok(None);
+        }
+
+        if !frame.is_data_frame() {
+            return Err(Error::NotADataFrame);
+        }
+
+        self.receive_id(raw_id(frame.id()), frame.data().expect("msg has data"))
+    }
+
+    /// Process a raw standard identifier and payload.
+    ///
+    /// Behaves like [`Mppt::receive`] for a data frame with the same contents,
+    /// for use with CAN peripherals other than bxcan. Returns `Ok(None)` for a
+    /// device on extended identifiers.
+    pub fn receive_raw(&mut self, id: u16, data: &[u8]) -> Result<Option<Broadcast>, Error> {
+        match self.id_kind {
+            IdKind::Standard => self.receive_id(id as u32, data),
+            IdKind::Extended => Ok(None),
+        }
+    }
+
+    /// Process a raw extended identifier and payload.
+    ///
+    /// The extended counterpart of [`Mppt::receive_raw`], returning `Ok(None)`
+    /// for a device on standard identifiers.
+    pub fn receive_raw_extended(
+        &mut self,
+        id: u32,
+        data: &[u8],
+    ) -> Result<Option<Broadcast>, Error> {
+        match self.id_kind {
+            IdKind::Extended => self.receive_id(id, data),
+            IdKind::Standard => Ok(None),
+        }
+    }
+
+    /// Decode a payload whose identifier is of the class of the device.
+    fn receive_id(&mut self, id: u32, data: &[u8]) -> Result<Option<Broadcast>, Error> {
+        match self.parse(id, data)? {
+            Some((message, data)) => Ok(Some(self.apply(message, data))),
+            None => Ok(None),
+        }
+    }
+
+    /// Apply a parsed message to the status.
+    fn apply(&mut self, message: &decode::Message, data: &[u8]) -> Broadcast {
+        self.reboot((message.rebooted)(&self.status, data));
+        decode::decode(message.broadcast, &mut self.status, data, &self.decoding);
+
+        if self.track_extremes {
+            self.extremes.record(message.broadcast, &self.status);
+        }
+
+        match message.broadcast {
+            Broadcast::Output => self
+                .output_voltage_variance
+                .record(self.status.output_voltage),
+            Broadcast::StatusFrame => {
+                let flags = self.status.limit_flags.unwrap_or(LimitFlags::empty());
+                self.limit_modes.record(flags & LIMIT_MODES);
             }
-            _ => {
-                return Err("message is not intended for this device");
+            _ => {}
+        }
+
+        message.broadcast
+    }
+
+    /// Decode a frame without updating the device.
+    ///
+    /// Returns the telemetry group and a status holding only the fields
+    /// carried by the frame, or `None` if the frame is not for this device.
+    /// Errors are the same as for [`Mppt::receive`].
+    pub fn peek(&self, frame: &Frame) -> Result<Option<(Broadcast, Status)>, Error> {
+        if !self.is_for_device(frame) {
+            return Ok(None);
+        }
+
+        if !frame.is_data_frame() {
+            return Err(Error::NotADataFrame);
+        }
+
+        let data = frame.data().expect("msg has data");
+        let (message, data) = match self.parse(raw_id(frame.id()), data)? {
+            Some(parsed) => parsed,
+            None => return Ok(None),
+        };
+
+        let mut status = Status::default();
+        decode::decode(message.broadcast, &mut status, data, &self.decoding);
+
+        Ok(Some((message.broadcast, status)))
+    }
+
+    /// Find the message for a raw identifier and strip the payload offset.
+    fn parse<'a>(
+        &self,
+        id: u32,
+        data: &'a [u8],
+    ) -> Result<Option<(&'static decode::Message, &'a [u8])>, Error> {
+        let offset = match self.offset(id) {
+            Some(offset) => offset,
+            None => return Ok(None),
+        };
+
+        let data = data.get(self.payload_offset..).unwrap_or_default();
+
+        let message = match decode::message(offset) {
+            Some(message) => message,
+            None => return Err(Error::UnhandledMessageId(offset)),
+        };
+
+        if data.len() < message.min_len {
+            return Err(Error::ShortPayload {
+                expected: message.min_len,
+                got: data.len(),
+            });
+        }
+
+        Ok(Some((message, data)))
+    }
+
+    /// Faults raised by the device, with the time their status frame was
+    /// received, oldest first.
+    ///
+    /// Each entry holds the error flags that were newly set by a status frame
+    /// processed with [`Mppt::receive_at`]. Only the last
+    /// [`FAULT_HISTORY_LEN`] onsets are kept. The history survives
+    /// [`Mppt::reset_status`], so a fault leading up to a reboot is kept, and
+    /// is only cleared when following another device.
+    pub fn recent_faults(&self) -> impl Iterator<Item = (u32, ErrorFlags)> + '_ {
+        self.recent_faults.iter()
+    }
+
+    /// Whether the device keeps switching between limiting the input current
+    /// and limiting the output voltage, which suggests the control loop is
+    /// oscillating at the boundary of the two modes.
+    ///
+    /// Looks at the limit flags of the last `window` status frames, up to
+    /// [`LIMIT_HISTORY_LEN`], ignoring frames with neither or both limits set.
+    /// Reports an oscillation once the active limit switched at least three
+    /// times, that is back and forth and back again.
+    pub fn limit_mode_oscillating(&self, window: usize) -> bool {
+        let recorded = self.limit_modes.iter().count();
+        let modes = self
+            .limit_modes
+            .iter()
+            .skip(recorded.saturating_sub(window))
+            .filter(|mode| !mode.is_empty() && *mode != LIMIT_MODES);
+
+        let mut switches = 0;
+        let mut previous = None;
+        for mode in modes {
+            if previous.is_some_and(|previous| previous != mode) {
+                switches += 1;
             }
+            previous = Some(mode);
         }
 
-        Ok(())
+        switches >= 3
+    }
+
+    /// Estimate the number of seconds until the MOSFET reaches `limit` degrees
+    /// Celsius at the current power loss.
+    ///
+    /// This uses a simple lumped thermal model: all of the power loss heats a
+    /// single thermal mass of `thermal_mass` joules per degree Celsius, and no
+    /// heat is lost to the surroundings. The estimate is therefore
+    /// pessimistic and only meaningful as an early warning.
+    ///
+    /// Returns `Some(0)` if the limit has already been reached, and `None` if
+    /// the temperature or power loss is unknown, there is no power loss or
+    /// the thermal mass is not positive.
+    pub fn thermal_runway_estimate(&self, thermal_mass: f32, limit: f32) -> Option<u32> {
+        let temperature = self.status.mostfet_temperature?;
+        let loss = self.status.power_loss()?;
+
+        if thermal_mass <= 0.0 || loss <= 0.0 {
+            return None;
+        }
+
+        let seconds = (limit - temperature) * thermal_mass / loss;
+        Some(seconds.max(0.0) as u32)
+    }
+
+    /// Maximum input current derated for the MOSFET temperature.
+    ///
+    /// The full `rated_max` is returned up to
+    /// [`NOMINAL_MOSFET_TEMPERATURE_MAX`], then reduced linearly to zero at
+    /// [`DERATE_MOSFET_TEMPERATURE_LIMIT`]. The result can be commanded with
+    /// [`Mppt::set_maximum_input_current`] to avoid overheating. Returns
+    /// `None` if the MOSFET temperature is unknown.
+    pub fn thermal_derated_max_input(&self, rated_max: f32) -> Option<f32> {
+        let temperature = self.status.mostfet_temperature?;
+        let span = DERATE_MOSFET_TEMPERATURE_LIMIT - NOMINAL_MOSFET_TEMPERATURE_MAX;
+        let fraction = (DERATE_MOSFET_TEMPERATURE_LIMIT - temperature) / span;
+
+        Some(rated_max * fraction.clamp(0.0, 1.0))
+    }
+
+    /// How far the output voltage is below the maximum output voltage, in
+    /// volts.
+    ///
+    /// A persistently large value means the array cannot reach the setpoint,
+    /// for example due to insufficient light. Returns `None` until both the
+    /// output and limits broadcasts have been received.
+    pub fn output_voltage_tracking_error(&self) -> Option<f32> {
+        Some(self.status.maximum_output_voltage? - self.status.output_voltage?)
+    }
+
+    /// Whether the frame is addressed to this device.
+    ///
+    /// True for identifiers of the configured class from the base identifier
+    /// up to the last command identifier, whether or not the message is
+    /// handled.
+    pub fn is_for_device(&self, frame: &Frame) -> bool {
+        self.frame_offset(frame).is_some()
+    }
+
+    /// Whether the frame identifier is a known broadcast or command of this
+    /// device.
+    ///
+    /// Useful to cheaply drop unrelated frames before calling
+    /// [`Mppt::receive`].
+    pub fn is_known_message(&self, frame: &Frame) -> bool {
+        self.frame_offset(frame)
+            .is_some_and(|offset| decode::message(offset).is_some())
+    }
+
+    /// Offset of the frame from the base identifier, if it is for this device.
+    fn frame_offset(&self, frame: &Frame) -> Option<u16> {
+        let kind = match frame.id() {
+            Id::Standard(_) => IdKind::Standard,
+            Id::Extended(_) => IdKind::Extended,
+        };
+
+        match kind == self.id_kind {
+            true => self.offset(raw_id(frame.id())),
+            false => None,
+        }
+    }
+
+    /// Offset of a raw identifier from the base identifier, if it is for this
+    /// device.
+    fn offset(&self, id: u32) -> Option<u16> {
+        let offset = id.checked_sub(self.base_id)?;
+
+        match offset <= ID_COMMAND_MAX_INPUT_CURERNT as u32 {
+            true => Some(offset as u16),
+            false => None,
+        }
+    }
+
+    /// Build a data frame at an offset from the base identifier.
+    ///
+    /// The base identifier is validated when it is set, except by
+    /// [`Mppt::new`].
+    fn frame(&self, offset: u16, data: impl Into<Data>) -> Frame {
+        let id = self.base_id + offset as u32;
+        let id: Id = match self.id_kind {
+            IdKind::Standard => u16::try_from(id)
+                .ok()
+                .and_then(StandardId::new)
+                .unwrap()
+                .into(),
+            IdKind::Extended => ExtendedId::new(id).unwrap().into(),
+        };
+
+        Frame::new_data(id, data)
+    }
+
+    /// Process an incoming message received at `now_ms`.
+    ///
+    /// Behaves like [`Mppt::receive`], additionally recording the time the
+    /// updated telemetry group was last received for [`Mppt::is_stale`]. The
+    /// timestamp is any monotonic millisecond counter and may wrap.
+    ///
+    /// Returns `Ok(None)` for a frame discarded by
+    /// [`Mppt::with_min_status_interval`].
+    pub fn receive_at(&mut self, frame: &Frame, now_ms: u32) -> Result<Option<Broadcast>, Error> {
+        if !self.is_for_device(frame) {
+            return Ok(None);
+        }
+
+        if !frame.is_data_frame() {
+            return Err(Error::NotADataFrame);
+        }
+
+        let data = frame.data().expect("msg has data");
+        let (message, data) = match self.parse(raw_id(frame.id()), data)? {
+            Some(parsed) => parsed,
+            None => return Ok(None),
+        };
+
+        let group = message.broadcast as usize;
+
+        let throttled = match (self.min_status_interval, self.last_applied[group]) {
+            (Some(interval), Some(applied)) => now_ms.wrapping_sub(applied) < interval,
+            _ => false,
+        };
+        if throttled {
+            // a long interval must not hide a counter wrap from reboot
+            // detection, so the counter is tracked for every frame
+            self.reboot((message.rebooted)(&self.status, data));
+            decode::track(message.broadcast, &mut self.status, data);
+            self.last_update[group] = Some(now_ms);
+            return Ok(None);
+        }
+
+        let faults = self.status.error_flags.unwrap_or(ErrorFlags::empty());
+        let broadcast = self.apply(message, data);
+
+        if let Some(errors) = self.status.error_flags {
+            if !(errors - faults).is_empty() {
+                self.recent_faults.record((now_ms, errors - faults));
+            }
+        }
+
+        // stamped after applying, as a detected reboot may reset the status
+        self.last_update[group] = Some(now_ms);
+        self.last_applied[group] = Some(now_ms);
+
+        match broadcast {
+            Broadcast::Input => self.input_power.record(now_ms, self.status.input_power()),
+            Broadcast::Output => self.output_power.record(now_ms, self.status.output_power()),
+            Broadcast::Temperature => self.record_mosfet_temperature(now_ms),
+            _ => {}
+        }
+
+        Ok(Some(broadcast))
+    }
+
+    /// Moving average of the input power in watts, weighted by how long each
+    /// reading was current.
+    ///
+    /// Each input broadcast processed with [`Mppt::receive_at`] is held until
+    /// the next one, so bursts of readings do not outweigh long steady
+    /// periods. Older time is discounted exponentially with the time constant
+    /// set by [`Mppt::with_input_power_time_constant`], so the average tracks
+    /// the current harvest rate without storing a history.
+    /// [`Mppt::reset_status`] only ends the current reading. Returns `None`
+    /// until two readings have been received.
+    pub fn time_weighted_input_power(&self) -> Option<f32> {
+        self.input_power.mean()
+    }
+
+    /// Process an incoming message received at `now_ms` and describe what
+    /// happened.
+    ///
+    /// Processes the frame as [`Mppt::receive_at`], which takes the timestamp
+    /// for the liveness of the group. Errors are the same as for
+    /// [`Mppt::receive`].
+    pub fn observe(&mut self, frame: &Frame, now_ms: u32) -> Result<Observation, Error> {
+        let before = self.status;
+        let last_update = self.last_update;
+
+        let group = self.receive_at(frame, now_ms)?;
+        let faults = |status: &Status| status.error_flags.unwrap_or(ErrorFlags::empty());
+
+        let changed = !self.observed.eq_within(&self.status, self.change_deadband);
+        if changed {
+            self.observed = self.status;
+        }
+
+        Ok(Observation {
+            group,
+            changed,
+            new_faults: faults(&self.status) - faults(&before),
+            previous_update: group.and_then(|g| last_update[g as usize]),
+        })
+    }
+
+    /// Estimate the number of seconds until the MOSFET reaches `limit` degrees
+    /// Celsius, extrapolating the current rate of rise.
+    ///
+    /// The rate is taken from the last two temperature broadcasts processed
+    /// with [`Mppt::receive_at`]. Returns `Some(0)` if the last temperature
+    /// has reached the limit, even while cooling. Otherwise returns `None` if
+    /// the temperature is stable or cooling, or fewer than two samples have
+    /// been received.
+    pub fn time_to_thermal_fault(&self, limit: f32) -> Option<u32> {
+        let (_, temperature) = self.mosfet_sample?;
+        if temperature >= limit {
+            return Some(0);
+        }
+        let rate = self.mosfet_rate.filter(|rate| *rate > 0.0)?;
+
+        Some(((limit - temperature) / rate).max(0.0) as u32)
+    }
+
+    fn record_mosfet_temperature(&mut self, now_ms: u32) {
+        let temperature = match self.status.mostfet_temperature {
+            Some(temperature) if temperature.is_finite() => temperature,
+            _ => return,
+        };
+
+        if let Some((time, previous)) = self.mosfet_sample {
+            let elapsed = now_ms.wrapping_sub(time) as f32 / 1000.0;
+            if elapsed > 0.0 {
+                self.mosfet_rate = Some((temperature - previous) / elapsed);
+            }
+        }
+
+        self.mosfet_sample = Some((now_ms, temperature));
+    }
+
+    /// Energy delivered at the output in watt hours.
+    ///
+    /// Accumulated from the output broadcasts processed with
+    /// [`Mppt::receive_at`], holding each reading until the next one, over
+    /// the whole session.
+    pub fn energy_harvested_wh(&self) -> f32 {
+        self.output_power.total / 3600.0
+    }
+
+    /// Process an incoming message and notify `sink` of the update.
+    ///
+    /// Behaves like [`Mppt::receive`]; the sink is only called when a
+    /// telemetry group was updated.
+    pub fn receive_into(
+        &mut self,
+        frame: &Frame,
+        sink: &mut impl StatusSink,
+    ) -> Result<Option<Broadcast>, Error> {
+        let broadcast = self.receive(frame)?;
+
+        if let Some(group) = broadcast {
+            sink.on_update(group, &self.status);
+        }
+
+        Ok(broadcast)
+    }
+
+    /// Extremes of the voltage and temperature channels since the last call
+    /// to [`Mppt::reset_extremes`].
+    ///
+    /// Only tracked when enabled with [`Mppt::with_extremes`]; non-finite
+    /// values are ignored.
+    pub fn extremes(&self) -> Extremes {
+        self.extremes
+    }
+
+    /// Standard deviation of the output voltage in volts.
+    ///
+    /// Exponentially weighted over roughly the last
+    /// [`OUTPUT_VOLTAGE_STDDEV_WINDOW`] output broadcasts since the status was
+    /// last reset, ignoring non-finite values, so an old excursion decays out.
+    /// A high value may indicate unstable tracking or a loose connection.
+    /// Returns `None` until two readings have been received.
+    pub fn output_voltage_stddev(&self) -> Option<f32> {
+        self.output_voltage_variance.std_dev()
+    }
+
+    /// Forget the tracked extremes, starting a new window.
+    pub fn reset_extremes(&mut self) {
+        self.extremes = Extremes::default();
+    }
+
+    /// Whether a telemetry group has not been updated within `timeout_ms`.
+    ///
+    /// Only updates processed with [`Mppt::receive_at`] are tracked; a group
+    /// that has never been received this way is stale.
+    pub fn is_stale(&self, group: Broadcast, now_ms: u32, timeout_ms: u32) -> bool {
+        match self.last_update.get(group as usize).copied().flatten() {
+            Some(time) => now_ms.wrapping_sub(time) > timeout_ms,
+            None => true,
+        }
+    }
+
+    /// Whether any broadcast group has been updated within `timeout_ms`.
+    ///
+    /// Command echoes are not counted, as frames at the command identifiers
+    /// may be sent by another node while the device is silent. As with
+    /// [`Mppt::is_stale`], only updates processed with [`Mppt::receive_at`]
+    /// are tracked.
+    pub fn is_online(&self, now_ms: u32, timeout_ms: u32) -> bool {
+        decode::MESSAGES
+            .iter()
+            .zip(self.last_update)
+            .filter(|(message, _)| !message.broadcast.is_command_echo())
+            .filter_map(|(_, time)| time)
+            .any(|time| now_ms.wrapping_sub(time) <= timeout_ms)
+    }
+
+    /// Record a detected reboot, clearing the status if configured to.
+    fn reboot(&mut self, rebooted: bool) {
+        if rebooted {
+            self.reboot_detected = true;
+            if self.reset_on_reboot {
+                self.reset_status();
+            }
+        }
+    }
+
+    /// Whether a device reboot has been detected since the last call.
+    ///
+    /// A reboot is detected when the test counter (or vendor uptime) goes
+    /// backwards between consecutive status frames. Wrapping around by at
+    /// most [`TEST_COUNTER_MAX_STEP`] counts, as when frames are lost near
+    /// the wrap, is not a reboot. Reading the flag clears it.
+    pub fn detected_reboot(&mut self) -> bool {
+        core::mem::take(&mut self.reboot_detected)
     }
 
     /// Get the current status of the MPPT.
@@ -139,35 +1516,217 @@ impl Mppt {
         self.status
     }
 
+    /// Take a copy of the current status for later analysis.
+    ///
+    /// Snapshots can be pushed into a fixed size buffer (such as
+    /// `heapless::HistoryBuffer`) and reduced with [`average_over`], oldest
+    /// first.
+    pub fn snapshot(&self) -> Status {
+        self.status
+    }
+
     /// Set the operating mode of the MPPT.
-    pub fn set_mode(self, mode: Mode) -> Frame {
-        let id = StandardId::new(self.base_id + ID_COMMAND_MODE).unwrap();
-        Frame::new_data(id, [mode as u8; 1])
+    pub fn set_mode(&mut self, mode: Mode) -> Frame {
+        self.commanded_mode = Some(mode);
+        self.frame(ID_COMMAND_MODE, [u8::from(mode); 1])
     }
 
     /// Set the maximum output voltage of the MPPT.
-    pub fn set_maximum_output_voltage(&mut self, voltage: f32) -> Frame {
-        let id = StandardId::new(self.base_id + ID_COMMAND_MAX_OUTPUT_VOLTAGE).unwrap();
-        Frame::new_data(id, voltage.to_le_bytes())
+    ///
+    /// The voltage must be within [`MAX_OUTPUT_VOLTAGE_RANGE`], and is then
+    /// converted with the configured [`CommandScaling`].
+    pub fn set_maximum_output_voltage(&mut self, voltage: f32) -> Result<Frame, Error> {
+        self.command(Command::MaxOutputVoltage(voltage))
     }
 
     /// Set the maximum input current of the MPPT.
-    pub fn set_maximum_input_current(&mut self, current: f32) -> Frame {
-        let id = StandardId::new(self.base_id + ID_COMMAND_MAX_INPUT_CURERNT).unwrap();
-        Frame::new_data(id, current.to_le_bytes())
+    ///
+    /// The current must be within [`MAX_INPUT_CURRENT_RANGE`], and is then
+    /// converted with the configured [`CommandScaling`].
+    pub fn set_maximum_input_current(&mut self, current: f32) -> Result<Frame, Error> {
+        self.command(Command::MaxInputCurrent(current))
+    }
+
+    /// Build the frame for a command.
+    ///
+    /// Setpoints outside of [`MAX_OUTPUT_VOLTAGE_RANGE`] or
+    /// [`MAX_INPUT_CURRENT_RANGE`] (including NaN) are rejected with
+    /// [`Error::OutOfRange`], valid setpoints are converted with the
+    /// configured [`CommandScaling`].
+    ///
+    /// The commanded mode and setpoints are tracked for
+    /// [`Mppt::export_config`] and [`Mppt::input_current_limit_applied`].
+    pub fn command(&mut self, cmd: Command) -> Result<Frame, Error> {
+        let scaling = self.command_scaling;
+
+        match cmd {
+            Command::Mode(mode) => Ok(self.set_mode(mode)),
+            Command::MaxOutputVoltage(voltage) => {
+                check_range(voltage, &MAX_OUTPUT_VOLTAGE_RANGE)?;
+                self.commanded_max_output_voltage = Some(voltage);
+                let voltage = scaling.maximum_output_voltage.apply(voltage);
+                Ok(self.frame(ID_COMMAND_MAX_OUTPUT_VOLTAGE, voltage.to_le_bytes()))
+            }
+            Command::MaxInputCurrent(current) => {
+                check_range(current, &MAX_INPUT_CURRENT_RANGE)?;
+                self.commanded_max_input_current = Some(current);
+                let current = scaling.maximum_input_current.apply(current);
+                Ok(self.frame(ID_COMMAND_MAX_INPUT_CURERNT, current.to_le_bytes()))
+            }
+        }
+    }
+
+    /// Build the frame for a command and enqueue it for transmission.
+    ///
+    /// Returns `nb::Error::WouldBlock` while every transmit mailbox holds a
+    /// frame of equal or higher priority. As with [`Tx::transmit`], a pending
+    /// lower priority frame may be replaced, in which case it is returned so
+    /// it can be queued again. Invalid setpoints are returned as
+    /// `nb::Error::Other`.
+    pub fn send<I: bxcan::Instance>(
+        &mut self,
+        tx: &mut Tx<I>,
+        cmd: Command,
+    ) -> nb::Result<Option<Frame>, Error> {
+        let frame = self.command(cmd).map_err(nb::Error::Other)?;
+
+        match tx.transmit(&frame) {
+            Ok(status) => Ok(status.dequeued_frame().cloned()),
+            Err(nb::Error::WouldBlock) => Err(nb::Error::WouldBlock),
+            Err(nb::Error::Other(never)) => match never {},
+        }
+    }
+
+    /// Capture the commanded mode and setpoints.
+    ///
+    /// Values commanded through this instance are used where known, otherwise
+    /// the echoes of the device (converted back to volts and amps with the
+    /// configured [`CommandScaling`]). Returns `None` until every setting is
+    /// known either way.
+    pub fn export_config(&self) -> Option<ConfigBlob> {
+        Some(ConfigBlob {
+            mode: self.commanded_mode()?,
+            max_output_voltage: self.commanded_max_output_voltage()?,
+            max_input_current: self.commanded_max_input_current()?,
+        })
+    }
+
+    /// Build the command frames reapplying a configuration.
+    ///
+    /// The frames set the mode, maximum output voltage and maximum input
+    /// current, in that order. Errors as for [`Mppt::command`].
+    pub fn apply_config(&mut self, blob: &ConfigBlob) -> Result<[Frame; 3], Error> {
+        Ok([
+            self.command(Command::Mode(blob.mode))?,
+            self.command(Command::MaxOutputVoltage(blob.max_output_voltage))?,
+            self.command(Command::MaxInputCurrent(blob.max_input_current))?,
+        ])
+    }
+
+    /// Whether the maximum input current reported in the limits broadcast
+    /// matches the commanded current.
+    ///
+    /// The current last commanded through this instance is used, or if none
+    /// was, the echo of the device (see
+    /// [`Status::commanded_max_input_current`]) converted back to amps. The
+    /// values match if they are within `tolerance` amps of each other.
+    /// Returns `None` until a current has been commanded and the limits
+    /// broadcast has been received.
+    pub fn input_current_limit_applied(&self, tolerance: f32) -> Option<bool> {
+        let commanded = self.commanded_max_input_current()?;
+        let reported = self.status.maximum_input_current?;
+
+        Some(libm::fabsf(reported - commanded) <= tolerance)
+    }
+
+    /// Mode commanded through this instance, otherwise as echoed.
+    fn commanded_mode(&self) -> Option<Mode> {
+        self.commanded_mode.or(self.status.commanded_mode)
+    }
+
+    /// Maximum output voltage commanded through this instance, otherwise as
+    /// echoed, in volts.
+    fn commanded_max_output_voltage(&self) -> Option<f32> {
+        self.commanded_max_output_voltage.or_else(|| {
+            let echo = self.status.commanded_max_output_voltage?;
+            self.command_scaling.maximum_output_voltage.invert(echo)
+        })
+    }
+
+    /// Maximum input current commanded through this instance, otherwise as
+    /// echoed, in amps.
+    fn commanded_max_input_current(&self) -> Option<f32> {
+        self.commanded_max_input_current.or_else(|| {
+            let echo = self.status.commanded_max_input_current?;
+            self.command_scaling.maximum_input_current.invert(echo)
+        })
     }
 }
 
-fn lower_float(data: &[u8]) -> Option<f32> {
-    match data[0..4].try_into() {
-        Ok(v) => Some(f32::from_le_bytes(v)),
-        Err(_) => None,
+/// Average a series of status snapshots.
+///
+/// Each floating point field is the mean of the snapshots where the value was
+/// present, or `None` if it was never present. All other fields (counters,
+/// flags, modes and the vendor uptime) are taken from the last snapshot, so
+/// the history must be ordered oldest first.
+///
+/// The slice of a `heapless::HistoryBuffer` is not in that order once the
+/// buffer has wrapped; collect its `oldest_ordered` iterator (for example into
+/// a `heapless::Vec`) first.
+pub fn average_over(history: &[Status]) -> Status {
+    let mut status = match history.last() {
+        Some(last) => *last,
+        None => return Status::default(),
+    };
+
+    status.input_voltage = mean(history, |s| s.input_voltage);
+    status.input_current = mean(history, |s| s.input_current);
+    status.output_voltage = mean(history, |s| s.output_voltage);
+    status.output_current = mean(history, |s| s.output_current);
+    status.mostfet_temperature = mean(history, |s| s.mostfet_temperature);
+    status.controller_temperature = mean(history, |s| s.controller_temperature);
+    status.rail_12v = mean(history, |s| s.rail_12v);
+    status.rail_3v = mean(history, |s| s.rail_3v);
+    status.maximum_output_voltage = mean(history, |s| s.maximum_output_voltage);
+    status.maximum_input_current = mean(history, |s| s.maximum_input_current);
+    status.power_connector_voltage = mean(history, |s| s.power_connector_voltage);
+    status.power_connector_temperature = mean(history, |s| s.power_connector_temperature);
+    status.commanded_max_output_voltage = mean(history, |s| s.commanded_max_output_voltage);
+    status.commanded_max_input_current = mean(history, |s| s.commanded_max_input_current);
+    #[cfg(feature = "vendor-extended-status")]
+    {
+        status.lifetime_energy_wh = mean(history, |s| s.lifetime_energy_wh);
     }
+
+    status
+}
+
+fn mean(history: &[Status], field: impl Fn(&Status) -> Option<f32>) -> Option<f32> {
+    let (sum, count) = history
+        .iter()
+        .filter_map(field)
+        .fold((0.0, 0u32), |(sum, count), v| (sum + v, count + 1));
+
+    match count {
+        0 => None,
+        n => Some(sum / n as f32),
+    }
+}
+
+fn check_range(value: f32, range: &RangeInclusive<f32>) -> Result<(), Error> {
+    match range.contains(&value) {
+        true => Ok(()),
+        false => Err(Error::OutOfRange { value }),
+    }
+}
+
+fn product(a: Option<f32>, b: Option<f32>) -> Option<f32> {
+    Some(a? * b?)
 }
 
-fn upper_float(data: &[u8]) -> Option<f32> {
-    match data[4..8].try_into() {
-        Ok(v) => Some(f32::from_le_bytes(v)),
-        Err(_) => None,
+fn raw_id(id: Id) -> u32 {
+    match id {
+        Id::Standard(id) => id.as_raw() as u32,
+        Id::Extended(id) => id.as_raw(),
     }
 }
@@ -0,0 +1,169 @@
+use crate::{Error, ErrorFlags, LimitFlags, Mode, Status};
+
+/// Version of the log record layout, stored in the first byte of a record.
+pub const LOG_VERSION: u8 = 2;
+
+/// Number of floating point fields in an encoded status.
+const WIRE_FLOATS: usize = 14;
+
+/// Number of single byte fields in an encoded status.
+const WIRE_BYTES: usize = 8;
+
+/// Length of a status encoded with [`Status::to_bytes`] in bytes.
+///
+/// Presence bitmap (4), floating point values (4 each) and the counter,
+/// flag and mode bytes (1 each).
+pub const STATUS_WIRE_LEN: usize = 4 + WIRE_FLOATS * 4 + WIRE_BYTES;
+
+/// Length of an encoded log record in bytes.
+///
+/// Version (1), sequence (2) and timestamp (4) followed by the encoded
+/// status.
+pub const LOG_RECORD_LEN: usize = 1 + 2 + 4 + STATUS_WIRE_LEN;
+
+impl Status {
+    /// Encode the status into a compact fixed size buffer.
+    ///
+    /// The buffer starts with a presence bitmap, followed by a fixed slot for
+    /// every encoded field: first the floating point fields, then the counter,
+    /// flag and mode bytes, each group in declaration order. Bit `n` of the
+    /// bitmap marks slot `n` as present. Absent fields are written as zero. All
+    /// multi-byte values are little-endian.
+    ///
+    /// The error and limit slots hold the raw bytes as received, including
+    /// reserved bits, or the flag bits if no raw byte is known. This format is
+    /// defined by this crate and is unrelated to the CAN wire format. Vendor
+    /// specific fields are not encoded.
+    pub fn to_bytes(&self) -> [u8; STATUS_WIRE_LEN] {
+        let mut buf = [0u8; STATUS_WIRE_LEN];
+        let mut presence = 0u32;
+        let mut pos = 4;
+
+        for (bit, value) in self.floats().into_iter().enumerate() {
+            if let Some(v) = value {
+                presence |= 1 << bit;
+                buf[pos..pos + 4].copy_from_slice(&v.to_le_bytes());
+            }
+            pos += 4;
+        }
+
+        for (bit, value) in bytes(self).into_iter().enumerate() {
+            if let Some(v) = value {
+                presence |= 1 << (WIRE_FLOATS + bit);
+                buf[pos] = v;
+            }
+            pos += 1;
+        }
+
+        buf[0..4].copy_from_slice(&presence.to_le_bytes());
+
+        buf
+    }
+
+    /// Decode a buffer produced by [`Status::to_bytes`].
+    ///
+    /// The error and limit flags are rebuilt from their raw bytes.
+    pub fn from_bytes(buf: &[u8; STATUS_WIRE_LEN]) -> Result<Status, Error> {
+        let presence = u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]);
+        if presence >> (WIRE_FLOATS + WIRE_BYTES) != 0 {
+            return Err(Error::MalformedStatus);
+        }
+
+        let present = |bit: usize| presence & (1 << bit) != 0;
+
+        let mut pos = 4;
+        let mut values = [None; WIRE_FLOATS];
+        for (bit, value) in values.iter_mut().enumerate() {
+            if present(bit) {
+                let v = [buf[pos], buf[pos + 1], buf[pos + 2], buf[pos + 3]];
+                *value = Some(f32::from_le_bytes(v));
+            }
+            pos += 4;
+        }
+
+        let byte = |index: usize| match present(WIRE_FLOATS + index) {
+            true => Some(buf[pos + index]),
+            false => None,
+        };
+
+        Ok(Status {
+            input_voltage: values[0],
+            input_current: values[1],
+            output_voltage: values[2],
+            output_current: values[3],
+            mostfet_temperature: values[4],
+            controller_temperature: values[5],
+            rail_12v: values[6],
+            rail_3v: values[7],
+            maximum_output_voltage: values[8],
+            maximum_input_current: values[9],
+            can_rx_error_count: byte(0),
+            can_tx_error_count: byte(1),
+            can_tx_overflow_count: byte(2),
+            error_flags: byte(3).map(ErrorFlags::from_bits_truncate),
+            limit_flags: byte(4).map(LimitFlags::from_bits_truncate),
+            raw_error_byte: byte(3),
+            raw_limit_byte: byte(4),
+            mode: byte(5).map(Mode::from),
+            test_counter: byte(6),
+            power_connector_voltage: values[10],
+            power_connector_temperature: values[11],
+            commanded_mode: byte(7).map(Mode::from),
+            commanded_max_output_voltage: values[12],
+            commanded_max_input_current: values[13],
+            #[cfg(feature = "vendor-extended-status")]
+            uptime_s: None,
+            #[cfg(feature = "vendor-extended-status")]
+            lifetime_energy_wh: None,
+        })
+    }
+
+    /// Encode the status into a compact record for persistent logging.
+    ///
+    /// The record starts with [`LOG_VERSION`], the sequence number and
+    /// timestamp, followed by the status as encoded by [`Status::to_bytes`].
+    pub fn encode_log(&self, seq: u16, ts: u32) -> [u8; LOG_RECORD_LEN] {
+        let mut record = [0u8; LOG_RECORD_LEN];
+
+        record[0] = LOG_VERSION;
+        record[1..3].copy_from_slice(&seq.to_le_bytes());
+        record[3..7].copy_from_slice(&ts.to_le_bytes());
+        record[7..].copy_from_slice(&self.to_bytes());
+
+        record
+    }
+
+    /// Decode a record produced by [`Status::encode_log`].
+    ///
+    /// Returns the sequence number, timestamp and status.
+    pub fn decode_log(record: &[u8; LOG_RECORD_LEN]) -> Result<(u16, u32, Status), Error> {
+        if record[0] != LOG_VERSION {
+            return Err(Error::UnsupportedLogVersion(record[0]));
+        }
+
+        let seq = u16::from_le_bytes([record[1], record[2]]);
+        let ts = u32::from_le_bytes([record[3], record[4], record[5], record[6]]);
+
+        let mut buf = [0u8; STATUS_WIRE_LEN];
+        buf.copy_from_slice(&record[7..]);
+
+        Ok((seq, ts, Status::from_bytes(&buf)?))
+    }
+}
+
+fn bytes(status: &Status) -> [Option<u8>; WIRE_BYTES] {
+    [
+        status.can_rx_error_count,
+        status.can_tx_error_count,
+        status.can_tx_overflow_count,
+        status
+            .raw_error_byte
+            .or(status.error_flags.map(|f| f.bits())),
+        status
+            .raw_limit_byte
+            .or(status.limit_flags.map(|f| f.bits())),
+        status.mode.map(u8::from),
+        status.test_counter,
+        status.commanded_mode.map(u8::from),
+    ]
+}
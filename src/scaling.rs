@@ -0,0 +1,102 @@
+use defmt::Format;
+
+/// Linear correction applied to a decoded value as `value * scale + offset`.
+#[derive(Format, Debug, PartialEq, Clone, Copy)]
+pub struct Linear {
+    pub scale: f32,
+    pub offset: f32,
+}
+
+impl Linear {
+    /// Correction that leaves the value unchanged.
+    pub const IDENTITY: Linear = Linear {
+        scale: 1.0,
+        offset: 0.0,
+    };
+
+    /// Apply the correction to a value.
+    pub const fn apply(&self, value: f32) -> f32 {
+        value * self.scale + self.offset
+    }
+
+    /// Undo the correction, returning `None` if the scale is zero.
+    pub fn invert(&self, value: f32) -> Option<f32> {
+        match self.scale {
+            0.0 => None,
+            scale => Some((value - self.offset) / scale),
+        }
+    }
+
+    pub(crate) const fn correct(&self, value: Option<f32>) -> Option<f32> {
+        match value {
+            Some(v) => Some(self.apply(v)),
+            None => None,
+        }
+    }
+}
+
+impl Default for Linear {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// Per-field corrections applied to measurements after they are decoded.
+///
+/// Useful for calibrating hardware revisions that scale sensors differently.
+#[derive(Format, Debug, PartialEq, Clone, Copy, Default)]
+pub struct ScalingProfile {
+    pub input_voltage: Linear,
+    pub input_current: Linear,
+    pub output_voltage: Linear,
+    pub output_current: Linear,
+    pub mostfet_temperature: Linear,
+    pub controller_temperature: Linear,
+    pub rail_12v: Linear,
+    pub rail_3v: Linear,
+    pub maximum_output_voltage: Linear,
+    pub maximum_input_current: Linear,
+    pub power_connector_voltage: Linear,
+    pub power_connector_temperature: Linear,
+}
+
+impl ScalingProfile {
+    /// Profile that leaves every value unchanged.
+    pub const fn identity() -> Self {
+        Self {
+            input_voltage: Linear::IDENTITY,
+            input_current: Linear::IDENTITY,
+            output_voltage: Linear::IDENTITY,
+            output_current: Linear::IDENTITY,
+            mostfet_temperature: Linear::IDENTITY,
+            controller_temperature: Linear::IDENTITY,
+            rail_12v: Linear::IDENTITY,
+            rail_3v: Linear::IDENTITY,
+            maximum_output_voltage: Linear::IDENTITY,
+            maximum_input_current: Linear::IDENTITY,
+            power_connector_voltage: Linear::IDENTITY,
+            power_connector_temperature: Linear::IDENTITY,
+        }
+    }
+}
+
+/// Conversions from engineering units to device native units applied to
+/// commanded setpoints before they are encoded.
+///
+/// The default is the identity, sending the setpoints as IEEE floats in volts
+/// and amps as documented in the manual.
+#[derive(Format, Debug, PartialEq, Clone, Copy, Default)]
+pub struct CommandScaling {
+    pub maximum_output_voltage: Linear,
+    pub maximum_input_current: Linear,
+}
+
+impl CommandScaling {
+    /// Scaling that sends setpoints unchanged.
+    pub const fn identity() -> Self {
+        Self {
+            maximum_output_voltage: Linear::IDENTITY,
+            maximum_input_current: Linear::IDENTITY,
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use bxcan::{Data, Frame};
+use socketcan::{CanFrame, EmbeddedFrame, ExtendedId, Id, StandardId};
+
+use crate::{Broadcast, Error, Mppt};
+
+/// Convert a frame, such as a command built by [`Mppt`], into a SocketCAN
+/// frame for sending from a Linux host.
+///
+/// The frame types of both crates are foreign to this crate, so the
+/// conversions are functions rather than `From` implementations.
+pub fn to_socketcan(frame: &Frame) -> CanFrame {
+    let id: Id = match frame.id() {
+        bxcan::Id::Standard(id) => StandardId::new(id.as_raw()).unwrap().into(),
+        bxcan::Id::Extended(id) => ExtendedId::new(id.as_raw()).unwrap().into(),
+    };
+
+    let frame = match frame.data() {
+        Some(data) => CanFrame::new(id, data),
+        None => CanFrame::new_remote(id, frame.dlc() as usize),
+    };
+    frame.expect("a CAN frame fits a SocketCAN frame")
+}
+
+/// Convert a SocketCAN frame into a frame for [`Mppt::receive`].
+///
+/// Returns `None` for error frames, which carry no message.
+pub fn from_socketcan(frame: &CanFrame) -> Option<Frame> {
+    let id: bxcan::Id = match frame.id() {
+        Id::Standard(id) => bxcan::StandardId::new(id.as_raw())?.into(),
+        Id::Extended(id) => bxcan::ExtendedId::new(id.as_raw())?.into(),
+    };
+
+    match frame {
+        CanFrame::Data(_) => Some(Frame::new_data(id, Data::new(frame.data())?)),
+        CanFrame::Remote(_) => Some(Frame::new_remote(id, frame.dlc() as u8)),
+        CanFrame::Error(_) => None,
+    }
+}
+
+impl Mppt {
+    /// Process a frame received from a SocketCAN socket.
+    ///
+    /// Behaves like [`Mppt::receive`]. Error frames are not for the device and
+    /// are ignored.
+    pub fn receive_socketcan(&mut self, frame: &CanFrame) -> Result<Option<Broadcast>, Error> {
+        match from_socketcan(frame) {
+            Some(frame) => self.receive(&frame),
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use core::fmt::{self, Write};
+
+use heapless::String;
+
+use crate::{Mode, Status};
+
+impl Status {
+    /// Format a compact one line summary of the output, such as
+    /// `On 151.2V 4.5A 97%`, for small displays or debug output.
+    ///
+    /// Shows the mode, output voltage, output current and efficiency, with `-`
+    /// for unknown values. A summary longer than `N` bytes is cut off.
+    pub fn summary_string<const N: usize>(&self) -> String<N> {
+        let mut summary = String::new();
+        let mut out = Truncating(&mut summary);
+
+        // writing to a truncating buffer never fails
+        let _ = match self.mode {
+            Some(Mode::On) => out.write_str("On"),
+            Some(Mode::Standby) => out.write_str("Standby"),
+            Some(Mode::Unknown(v)) => write!(out, "Mode {v}"),
+            None => out.write_str("-"),
+        };
+        let _ = field(&mut out, self.output_voltage, "V");
+        let _ = field(&mut out, self.output_current, "A");
+        let _ = match self.efficiency() {
+            Some(efficiency) => write!(out, " {:.0}%", efficiency * 100.0),
+            None => out.write_str(" -%"),
+        };
+
+        summary
+    }
+}
+
+/// Write a value with one decimal followed by its unit.
+fn field(out: &mut impl Write, value: Option<f32>, unit: &str) -> fmt::Result {
+    match value {
+        Some(value) => write!(out, " {value:.1}{unit}"),
+        None => write!(out, " -{unit}"),
+    }
+}
+
+/// Writer dropping whatever does not fit in the string.
+struct Truncating<'a, const N: usize>(&'a mut String<N>);
+
+impl<const N: usize> Write for Truncating<'_, N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            if self.0.push(c).is_err() {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
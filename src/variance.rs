@@ -0,0 +1,56 @@
+use defmt::Format;
+
+/// Exponentially weighted variance of a value, updated one sample at a time.
+///
+/// The first samples are weighted equally, as with Welford's algorithm. Once
+/// `window` samples have been recorded each new sample has a weight of
+/// `1 / window`, so older samples fade out without storing a history.
+#[derive(Format, Debug, Default, Clone, Copy)]
+pub(crate) struct Variance {
+    /// Number of samples after which the weights stop shrinking.
+    window: u32,
+    /// Number of samples recorded, up to the window.
+    count: u32,
+    /// Weighted mean of the samples.
+    mean: f32,
+    /// Weighted population variance of the samples.
+    variance: f32,
+}
+
+impl Variance {
+    /// Variance weighting roughly the last `samples` samples.
+    pub fn windowed(samples: u32) -> Self {
+        Self {
+            window: samples.max(1),
+            ..Self::default()
+        }
+    }
+
+    /// Forget the recorded samples, keeping the window.
+    pub fn clear(&mut self) {
+        *self = Self::windowed(self.window);
+    }
+
+    /// Add a sample, ignoring unknown and non-finite values.
+    pub fn record(&mut self, value: Option<f32>) {
+        let value = match value {
+            Some(value) if value.is_finite() => value,
+            _ => return,
+        };
+
+        self.count = (self.count + 1).min(self.window);
+        let weight = 1.0 / self.count as f32;
+        let delta = value - self.mean;
+        self.mean += weight * delta;
+        self.variance = (1.0 - weight) * (self.variance + weight * delta * delta);
+    }
+
+    /// Population standard deviation of the samples, if at least two have
+    /// been recorded.
+    pub fn std_dev(&self) -> Option<f32> {
+        match self.count >= 2 {
+            true => Some(libm::sqrtf(self.variance)),
+            false => None,
+        }
+    }
+}
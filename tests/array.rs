@@ -0,0 +1,162 @@
+//! Aggregation and dispatch across an `MpptArray`.
+
+mod common;
+
+use bxcan::StandardId;
+use common::*;
+use elmar_mppt::*;
+
+fn devices<const N: usize>() -> MpptArray<N> {
+    MpptArray::new(core::array::from_fn(|i| {
+        Mppt::from_switch(i as u8).unwrap()
+    }))
+}
+
+#[test]
+fn online_count_skips_stale_devices() {
+    let mut array = devices::<3>();
+    assert_eq!(array.online_count(0, 1000), 0);
+
+    let input = floats(100.0, 2.5);
+    array
+        .receive_at(&frame_at(ID_BASE, ID_BROADCAST_INPUT, input), 1000)
+        .unwrap();
+    array
+        .receive_at(&frame_at(ID_BASE + ID_INC, ID_BROADCAST_INPUT, input), 4000)
+        .unwrap();
+
+    assert_eq!(array.online_count(4500, 1000), 1);
+    assert_eq!(array.online_count(4500, 5000), 2);
+
+    // a command to a silent device is not a sign of life
+    let command = Mppt::new(base(2)).set_mode(Mode::On);
+    array.receive_at(&command, 4500).unwrap();
+    assert_eq!(array.online_count(4500, 1000), 1);
+}
+
+/// Base identifier of the device at a switch position.
+fn base(position: u16) -> u16 {
+    ID_BASE + ID_INC * position
+}
+
+#[test]
+fn combined_status_aggregation_rules() {
+    let mut array = devices::<2>();
+    let frames = [
+        frame_at(base(0), ID_BROADCAST_INPUT, floats(100.0, 2.0)),
+        frame_at(base(1), ID_BROADCAST_INPUT, floats(120.0, 3.0)),
+        frame_at(base(0), ID_BROADCAST_TEMPERATURE, floats(40.0, 30.0)),
+        frame_at(base(1), ID_BROADCAST_TEMPERATURE, floats(55.0, 25.0)),
+        frame_at(
+            base(0),
+            ID_BROADCAST_STATUS,
+            [10, 0, 0, 0x01, 0x01, 1, 0, 7],
+        ),
+        frame_at(
+            base(1),
+            ID_BROADCAST_STATUS,
+            [250, 0, 0, 0x10, 0x02, 0, 0, 9],
+        ),
+        // only the first device reports its output
+        frame_at(base(0), ID_BROADCAST_OUTPUT, floats(160.0, 1.0)),
+    ];
+    for frame in &frames {
+        array.receive(frame).unwrap();
+    }
+
+    let status = array.combined_status();
+    assert_eq!(status.input_voltage, Some(110.0));
+    assert_eq!(status.input_current, Some(5.0));
+    assert_eq!(status.mostfet_temperature, Some(55.0));
+    assert_eq!(status.controller_temperature, Some(30.0));
+    assert_eq!(
+        status.error_flags,
+        Some(ErrorFlags::HW_OVERVOLTAGE | ErrorFlags::BATTERY_FULL)
+    );
+    assert_eq!(
+        status.limit_flags,
+        Some(LimitFlags::GLOBAL_MPPT | LimitFlags::LOCAL_MPPT)
+    );
+    assert_eq!(status.can_rx_error_count, Some(u8::MAX));
+    // the devices disagree on the mode
+    assert_eq!(status.mode, None);
+    assert_eq!(status.test_counter, None);
+    // missing values are left out rather than treated as zero
+    assert_eq!(status.output_voltage, Some(160.0));
+    assert_eq!(status.output_current, Some(1.0));
+    assert_eq!(status.rail_12v, None);
+}
+
+#[test]
+fn contribution_of_unequal_devices() {
+    let mut array = devices::<2>();
+    assert_eq!(array.contribution(0), None);
+
+    // 100 W and 300 W
+    array
+        .receive(&frame_at(base(0), ID_BROADCAST_OUTPUT, floats(100.0, 1.0)))
+        .unwrap();
+    assert_eq!(array.contribution(0), None, "second device missing");
+    array
+        .receive(&frame_at(base(1), ID_BROADCAST_OUTPUT, floats(100.0, 3.0)))
+        .unwrap();
+
+    assert_eq!(array.contribution(0), Some(0.25));
+    assert_eq!(array.contribution(1), Some(0.75));
+    assert_eq!(array.contribution(2), None);
+
+    let mut idle = devices::<2>();
+    for position in 0..2 {
+        idle.receive(&frame_at(
+            base(position),
+            ID_BROADCAST_OUTPUT,
+            floats(100.0, 0.0),
+        ))
+        .unwrap();
+    }
+    assert_eq!(idle.contribution(0), None, "zero total power");
+}
+
+#[test]
+fn set_mode_all_addresses_every_device() {
+    let mut array = devices::<3>();
+    let frames = array.set_mode_all(Mode::Standby);
+
+    for (position, frame) in frames.iter().enumerate() {
+        let id = StandardId::new(base(position as u16) + ID_COMMAND_MODE).unwrap();
+        assert_eq!(frame.id(), id.into());
+        assert_eq!(frame.data().unwrap().as_ref(), [u8::from(Mode::Standby)]);
+    }
+}
+
+#[test]
+fn iter_yields_each_device_in_order() {
+    let mut array = MpptArray::new([Mppt::new(base(2)), Mppt::new(base(5))]);
+    array
+        .receive(&frame_at(base(5), ID_BROADCAST_INPUT, floats(100.0, 2.0)))
+        .unwrap();
+
+    let ids: Vec<u16> = array.iter().map(|(id, _)| id).collect();
+    assert_eq!(ids, [base(2), base(5)]);
+
+    let voltages: Vec<Option<f32>> = array.iter().map(|(_, s)| s.input_voltage).collect();
+    assert_eq!(voltages, [None, Some(100.0)]);
+}
+
+#[test]
+fn total_energy_of_two_devices() {
+    let mut array = devices::<2>();
+    assert_eq!(array.total_energy_harvested(), 0.0);
+
+    // 360 W for 20 s is 2 Wh, and 720 W for 10 s is another 2 Wh
+    let first = frame_at(base(0), ID_BROADCAST_OUTPUT, floats(120.0, 3.0));
+    array.receive_at(&first, 0).unwrap();
+    array.receive_at(&first, 20_000).unwrap();
+    let second = frame_at(base(1), ID_BROADCAST_OUTPUT, floats(120.0, 6.0));
+    array.receive_at(&second, 5_000).unwrap();
+    array.receive_at(&second, 15_000).unwrap();
+
+    assert_eq!(array.get(0).unwrap().energy_harvested_wh(), 2.0);
+    assert_eq!(array.get(1).unwrap().energy_harvested_wh(), 2.0);
+    assert_eq!(array.total_energy_harvested(), 4.0);
+}
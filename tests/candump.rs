@@ -0,0 +1,133 @@
+//! Parsing `candump` log lines for replay.
+#![cfg(feature = "std")]
+
+use elmar_mppt::*;
+
+#[test]
+fn full_candump_line_is_parsed_and_decoded() {
+    let line = "(1700000000.123456) can0  600   [8]  00 00 C8 42 00 00 20 40";
+    let (id, data, dlc) = parse_candump_line(line).unwrap();
+
+    assert_eq!(id, 0x600);
+    assert_eq!(data, [0x00, 0x00, 0xC8, 0x42, 0x00, 0x00, 0x20, 0x40]);
+    assert_eq!(dlc, 8);
+
+    let mut mppt = Mppt::new(ID_BASE);
+    assert_eq!(
+        mppt.receive_raw(id, &data[..dlc]),
+        Ok(Some(Broadcast::Input))
+    );
+    assert_eq!(mppt.snapshot().input_voltage, Some(100.0));
+}
+
+#[test]
+fn short_dlc_candump_line_is_zero_padded() {
+    let (id, data, dlc) = parse_candump_line("can0  608   [1]  01").unwrap();
+
+    assert_eq!(id, 0x608);
+    assert_eq!(data, [0x01, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(dlc, 1);
+
+    let mut mppt = Mppt::new(ID_BASE);
+    assert_eq!(
+        mppt.receive_raw(id, &data[..dlc]),
+        Ok(Some(Broadcast::CommandMode))
+    );
+}
+
+#[test]
+fn malformed_candump_lines_are_rejected() {
+    for line in [
+        "",
+        "can0  600   [2]  00",
+        "can0  600   [1]  00 11",
+        "can0  18000600   [1]  00",
+        "can0  600   [9]  00 00 00 00 00 00 00 00 00",
+    ] {
+        assert_eq!(parse_candump_line(line), None, "{line:?}");
+    }
+}
+
+/// Broadcast line at `id` carrying two floats.
+fn floats_line(id: u16, lower: f32, upper: f32) -> String {
+    let bytes: Vec<String> = lower
+        .to_le_bytes()
+        .iter()
+        .chain(&upper.to_le_bytes())
+        .map(|b| format!("{b:02X}"))
+        .collect();
+    format!("can0  {id:03X}   [8]  {}", bytes.join(" "))
+}
+
+/// Input broadcast line for a voltage and current.
+fn input_line(voltage: f32, current: f32) -> String {
+    floats_line(ID_BASE + ID_BROADCAST_INPUT, voltage, current)
+}
+
+/// Output broadcast line for a voltage and current.
+fn output_line(voltage: f32, current: f32) -> String {
+    floats_line(ID_BASE + ID_BROADCAST_OUTPUT, voltage, current)
+}
+
+#[test]
+fn logs_differing_by_a_scale_factor() {
+    let samples = [(100.0, 2.0), (110.0, 2.5), (120.0, 3.0)];
+    let log = |scale: f32| {
+        samples
+            .iter()
+            .map(|&(v, i)| input_line(v * scale, i))
+            .chain(["not a candump line".to_string()])
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let deviations = compare_candump_logs(ID_BASE, &log(1.0), &log(1.1));
+
+    // only the fields present in both logs are reported
+    let fields: Vec<&str> = deviations.iter().map(|d| d.field).collect();
+    assert_eq!(fields, ["input_voltage", "input_current"]);
+
+    let voltage = deviations[0];
+    assert_eq!(voltage.samples, 3);
+    assert!((voltage.mean - 11.0).abs() < 1e-3, "{voltage:?}");
+    assert!((voltage.max - 12.0).abs() < 1e-3, "{voltage:?}");
+
+    let current = deviations[1];
+    assert_eq!((current.mean, current.max), (0.0, 0.0));
+}
+
+#[test]
+fn logs_interleaving_the_groups_differently() {
+    let before = [
+        input_line(100.0, 2.0),
+        output_line(150.0, 1.0),
+        input_line(110.0, 2.0),
+        output_line(160.0, 1.0),
+    ]
+    .join("\n");
+    let after = [
+        output_line(151.0, 1.0),
+        output_line(161.0, 1.0),
+        input_line(100.0, 2.0),
+        input_line(110.0, 2.0),
+    ]
+    .join("\n");
+
+    let deviations = compare_candump_logs(ID_BASE, &before, &after);
+
+    let fields: Vec<&str> = deviations.iter().map(|d| d.field).collect();
+    assert_eq!(
+        fields,
+        [
+            "input_voltage",
+            "input_current",
+            "output_voltage",
+            "output_current"
+        ]
+    );
+    for deviation in &deviations {
+        assert_eq!(deviation.samples, 2, "{deviation:?}");
+    }
+    assert_eq!((deviations[0].mean, deviations[0].max), (0.0, 0.0));
+    assert_eq!((deviations[2].mean, deviations[2].max), (1.0, 1.0));
+}
@@ -0,0 +1,247 @@
+//! Command frames and the device echoes of commanded setpoints.
+
+mod common;
+
+use bxcan::{Frame, StandardId};
+use common::*;
+use elmar_mppt::*;
+
+/// Limits broadcast reporting 160 V and the given maximum input current.
+fn limits(current: f32) -> Frame {
+    frame(ID_BROADCAST_LIMITS, floats(160.0, current))
+}
+
+#[test]
+fn command_echoes_are_decoded() {
+    let mut mppt = Mppt::new(ID_BASE);
+
+    let mode = frame(ID_COMMAND_MODE, [1]);
+    let voltage = frame(ID_COMMAND_MAX_OUTPUT_VOLTAGE, 48.5f32.to_le_bytes());
+    let current = frame(ID_COMMAND_MAX_INPUT_CURERNT, 8.0f32.to_le_bytes());
+
+    assert_eq!(mppt.receive(&mode), Ok(Some(Broadcast::CommandMode)));
+    assert_eq!(
+        mppt.receive(&voltage),
+        Ok(Some(Broadcast::CommandMaxOutputVoltage))
+    );
+    assert_eq!(
+        mppt.receive(&current),
+        Ok(Some(Broadcast::CommandMaxInputCurrent))
+    );
+
+    let status = mppt.snapshot();
+    assert_eq!(status.commanded_mode, Some(Mode::On));
+    assert_eq!(status.commanded_max_output_voltage, Some(48.5));
+    assert_eq!(status.commanded_max_input_current, Some(8.0));
+}
+
+#[test]
+fn input_current_limit_tracks_the_command() {
+    let mut mppt = Mppt::new(ID_BASE);
+    assert_eq!(mppt.input_current_limit_applied(0.1), None);
+
+    // a node does not receive its own transmissions, so there is no echo
+    mppt.command(Command::MaxInputCurrent(8.0)).unwrap();
+    assert_eq!(mppt.input_current_limit_applied(0.1), None);
+
+    mppt.receive(&limits(8.0)).unwrap();
+    assert_eq!(mppt.input_current_limit_applied(0.1), Some(true));
+
+    // rejected setpoints are not tracked
+    assert!(mppt.set_maximum_input_current(-1.0).is_err());
+    assert_eq!(mppt.input_current_limit_applied(0.1), Some(true));
+}
+
+#[test]
+fn input_current_limit_falls_back_to_the_echo() {
+    // another node commanded the device
+    let mut mppt = Mppt::new(ID_BASE);
+    mppt.receive(&frame(ID_COMMAND_MAX_INPUT_CURERNT, 5.0f32.to_le_bytes()))
+        .unwrap();
+    mppt.receive(&limits(5.0)).unwrap();
+    assert_eq!(mppt.input_current_limit_applied(0.1), Some(true));
+
+    // a command through this instance takes precedence
+    mppt.set_maximum_input_current(8.0).unwrap();
+    assert_eq!(mppt.input_current_limit_applied(0.1), Some(false));
+}
+
+#[test]
+fn echoed_setpoint_reads_back_without_tracking() {
+    let mut mppt = Mppt::new(ID_BASE);
+
+    // the device clamped a commanded 12 A to 10 A
+    let echo = 10.0f32.to_le_bytes();
+    mppt.receive_raw(ID_BASE + ID_COMMAND_MAX_INPUT_CURERNT, &echo)
+        .unwrap();
+
+    assert_eq!(mppt.snapshot().commanded_max_input_current, Some(10.0));
+    assert_ne!(mppt.snapshot().commanded_max_input_current, Some(12.0));
+}
+
+#[test]
+fn command_builds_each_frame() {
+    let mut mppt = Mppt::new(ID_BASE);
+    let id = |offset| bxcan::Id::Standard(StandardId::new(ID_BASE + offset).unwrap());
+
+    let mode = mppt.command(Command::Mode(Mode::On)).unwrap();
+    assert_eq!(mode.id(), id(ID_COMMAND_MODE));
+    assert_eq!(mode.data().unwrap().as_ref(), &[1]);
+    assert_eq!(mode, mppt.set_mode(Mode::On));
+
+    let voltage = mppt.command(Command::MaxOutputVoltage(160.0)).unwrap();
+    assert_eq!(voltage.id(), id(ID_COMMAND_MAX_OUTPUT_VOLTAGE));
+    assert_eq!(voltage.data().unwrap().as_ref(), &160.0f32.to_le_bytes());
+    assert_eq!(voltage, mppt.set_maximum_output_voltage(160.0).unwrap());
+
+    let current = mppt.command(Command::MaxInputCurrent(8.0)).unwrap();
+    assert_eq!(current.id(), id(ID_COMMAND_MAX_INPUT_CURERNT));
+    assert_eq!(current.data().unwrap().as_ref(), &8.0f32.to_le_bytes());
+    assert_eq!(current, mppt.set_maximum_input_current(8.0).unwrap());
+}
+
+#[test]
+fn setpoints_are_range_checked() {
+    let mut mppt = Mppt::new(ID_BASE);
+
+    assert!(matches!(
+        mppt.set_maximum_output_voltage(f32::NAN),
+        Err(Error::OutOfRange { value }) if value.is_nan()
+    ));
+    assert!(matches!(
+        mppt.set_maximum_input_current(f32::NAN),
+        Err(Error::OutOfRange { value }) if value.is_nan()
+    ));
+    assert_eq!(
+        mppt.set_maximum_input_current(-1.0),
+        Err(Error::OutOfRange { value: -1.0 })
+    );
+    assert_eq!(
+        mppt.set_maximum_output_voltage(-0.5),
+        Err(Error::OutOfRange { value: -0.5 })
+    );
+    assert!(!MAX_INPUT_CURRENT_RANGE.contains(&f32::INFINITY));
+
+    let voltage = mppt.set_maximum_output_voltage(80.0).unwrap();
+    assert_eq!(
+        voltage,
+        frame(ID_COMMAND_MAX_OUTPUT_VOLTAGE, 80.0f32.to_le_bytes())
+    );
+    let current = mppt.set_maximum_input_current(4.0).unwrap();
+    assert_eq!(
+        current,
+        frame(ID_COMMAND_MAX_INPUT_CURERNT, 4.0f32.to_le_bytes())
+    );
+}
+
+#[test]
+fn command_scaling_converts_to_native_units() {
+    // a device taking setpoints in DAC counts of 10 mV and 1 mA
+    let mut mppt = Mppt::new(ID_BASE).with_command_scaling(CommandScaling {
+        maximum_output_voltage: Linear {
+            scale: 100.0,
+            offset: 0.0,
+        },
+        maximum_input_current: Linear {
+            scale: 1000.0,
+            offset: 5.0,
+        },
+    });
+
+    let voltage = mppt.set_maximum_output_voltage(160.0).unwrap();
+    assert_eq!(voltage.data().unwrap().as_ref(), &16000.0f32.to_le_bytes());
+    let current = mppt.set_maximum_input_current(8.0).unwrap();
+    assert_eq!(current.data().unwrap().as_ref(), &8005.0f32.to_le_bytes());
+
+    // the range applies to the engineering units
+    assert_eq!(
+        mppt.set_maximum_input_current(-0.001),
+        Err(Error::OutOfRange { value: -0.001 })
+    );
+}
+
+#[test]
+fn input_current_limit_matching_and_mismatching() {
+    let mut mppt = Mppt::new(ID_BASE);
+    mppt.set_maximum_input_current(8.0).unwrap();
+
+    mppt.receive(&limits(7.95)).unwrap();
+    assert_eq!(mppt.input_current_limit_applied(0.1), Some(true));
+    assert_eq!(mppt.input_current_limit_applied(0.01), Some(false));
+
+    // the device is still running the previous limit
+    mppt.receive(&limits(5.0)).unwrap();
+    assert_eq!(mppt.input_current_limit_applied(0.1), Some(false));
+}
+
+#[test]
+fn input_current_limit_converts_the_echo_to_amps() {
+    // a device taking the current setpoint in milliamps
+    let mut mppt = Mppt::new(ID_BASE).with_command_scaling(CommandScaling {
+        maximum_input_current: Linear {
+            scale: 1000.0,
+            offset: 0.0,
+        },
+        ..CommandScaling::identity()
+    });
+
+    // echo of a command sent by another node
+    mppt.receive(&frame(
+        ID_COMMAND_MAX_INPUT_CURERNT,
+        8000.0f32.to_le_bytes(),
+    ))
+    .unwrap();
+    assert_eq!(mppt.snapshot().commanded_max_input_current, Some(8000.0));
+
+    mppt.receive(&limits(8.0)).unwrap();
+    assert_eq!(mppt.input_current_limit_applied(0.1), Some(true));
+    mppt.receive(&limits(5.0)).unwrap();
+    assert_eq!(mppt.input_current_limit_applied(0.1), Some(false));
+}
+
+#[test]
+fn config_round_trip() {
+    let mut mppt = Mppt::new(ID_BASE).with_command_scaling(CommandScaling {
+        maximum_output_voltage: Linear {
+            scale: 100.0,
+            offset: 0.0,
+        },
+        ..CommandScaling::identity()
+    });
+    let commands = [
+        Command::Mode(Mode::On),
+        Command::MaxOutputVoltage(150.0),
+        Command::MaxInputCurrent(7.0),
+    ];
+
+    assert_eq!(mppt.export_config(), None);
+    let sent = commands.map(|cmd| mppt.command(cmd).unwrap());
+
+    let blob = mppt.export_config().unwrap();
+    assert_eq!(
+        blob,
+        ConfigBlob {
+            mode: Mode::On,
+            max_output_voltage: 150.0,
+            max_input_current: 7.0,
+        }
+    );
+
+    // reapplied to a freshly reset device
+    mppt.reset_status();
+    assert_eq!(mppt.apply_config(&blob).as_ref(), Ok(&sent));
+
+    // echoes fill in settings not commanded through the instance
+    let mut echoed = Mppt::new(ID_BASE).with_command_scaling(CommandScaling {
+        maximum_output_voltage: Linear {
+            scale: 100.0,
+            offset: 0.0,
+        },
+        ..CommandScaling::identity()
+    });
+    echoed.set_maximum_input_current(7.0).unwrap();
+    for frame in &sent[..2] {
+        echoed.receive(frame).unwrap();
+    }
+    assert_eq!(echoed.export_config(), Some(blob));
+}
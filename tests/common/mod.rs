@@ -0,0 +1,94 @@
+//! Helpers shared by the integration tests.
+
+// each test crate uses a different subset of the helpers
+#![allow(dead_code)]
+
+use bxcan::{Data, Frame, StandardId};
+use elmar_mppt::*;
+
+/// Offset and group of every message the driver decodes, broadcasts first.
+pub const MESSAGES: &[(u16, Broadcast)] = &[
+    (ID_BROADCAST_INPUT, Broadcast::Input),
+    (ID_BROADCAST_OUTPUT, Broadcast::Output),
+    (ID_BROADCAST_TEMPERATURE, Broadcast::Temperature),
+    (ID_BROADCAST_AUX_POWER, Broadcast::AuxPower),
+    (ID_BROADCAST_LIMITS, Broadcast::Limits),
+    (ID_BROADCAST_STATUS, Broadcast::StatusFrame),
+    (ID_BROADCAST_POWER_CONNECTOR, Broadcast::PowerConnector),
+    #[cfg(feature = "vendor-extended-status")]
+    (ID_BROADCAST_EXTENDED_STATUS, Broadcast::ExtendedStatus),
+    (ID_COMMAND_MODE, Broadcast::CommandMode),
+    (
+        ID_COMMAND_MAX_OUTPUT_VOLTAGE,
+        Broadcast::CommandMaxOutputVoltage,
+    ),
+    (
+        ID_COMMAND_MAX_INPUT_CURERNT,
+        Broadcast::CommandMaxInputCurrent,
+    ),
+];
+
+/// Two little-endian floats packed into a broadcast payload.
+pub fn floats(lower: f32, upper: f32) -> [u8; 8] {
+    let mut data = [0u8; 8];
+    data[..4].copy_from_slice(&lower.to_le_bytes());
+    data[4..].copy_from_slice(&upper.to_le_bytes());
+    data
+}
+
+/// Status broadcast payload with the given error byte, mode byte and test
+/// counter, and every other byte zero.
+pub fn status_payload(errors: u8, mode: u8, counter: u8) -> [u8; 8] {
+    [0, 0, 0, errors, 0, mode, 0, counter]
+}
+
+/// Data frame at `offset` from the base identifier `base`.
+pub fn frame_at(base: u16, offset: u16, data: impl AsRef<[u8]>) -> Frame {
+    let id = StandardId::new(base + offset).unwrap();
+    Frame::new_data(id, Data::new(data.as_ref()).unwrap())
+}
+
+/// Data frame at `offset` from [`ID_BASE`].
+pub fn frame(offset: u16, data: impl AsRef<[u8]>) -> Frame {
+    frame_at(ID_BASE, offset, data)
+}
+
+/// Process a payload at `offset` from the base identifier of the device.
+pub fn receive(mppt: &mut Mppt, offset: u16, data: impl AsRef<[u8]>) {
+    mppt.receive_raw(mppt.base_id() + offset, data.as_ref())
+        .unwrap();
+}
+
+/// Fully populated status with recognizable values.
+pub fn golden() -> Status {
+    Status {
+        input_voltage: Some(100.0),
+        input_current: Some(2.5),
+        output_voltage: Some(160.0),
+        output_current: Some(1.5),
+        mostfet_temperature: Some(40.0),
+        controller_temperature: Some(35.0),
+        rail_12v: Some(12.0),
+        rail_3v: Some(3.25),
+        maximum_output_voltage: Some(165.0),
+        maximum_input_current: Some(8.0),
+        can_rx_error_count: Some(1),
+        can_tx_error_count: Some(2),
+        can_tx_overflow_count: Some(3),
+        error_flags: Some(ErrorFlags::BATTERY_FULL),
+        limit_flags: Some(LimitFlags::GLOBAL_MPPT),
+        raw_error_byte: Some(ErrorFlags::BATTERY_FULL.bits()),
+        raw_limit_byte: Some(LimitFlags::GLOBAL_MPPT.bits()),
+        mode: Some(Mode::On),
+        test_counter: Some(42),
+        power_connector_voltage: Some(160.0),
+        power_connector_temperature: Some(25.0),
+        commanded_mode: Some(Mode::On),
+        commanded_max_output_voltage: Some(165.0),
+        commanded_max_input_current: Some(8.0),
+        #[cfg(feature = "vendor-extended-status")]
+        uptime_s: Some(3600),
+        #[cfg(feature = "vendor-extended-status")]
+        lifetime_energy_wh: Some(1000.0),
+    }
+}
@@ -0,0 +1,38 @@
+//! Cross-check the protocol constants against the Elmar MPPT user manual.
+
+use elmar_mppt::*;
+
+#[test]
+fn identifiers_match_manual() {
+    // (name, value in crate, value from the manual)
+    let expected: &[(&str, u16, u16)] = &[
+        ("ID_BASE", ID_BASE, 0x600),
+        ("ID_INC", ID_INC, 0x10),
+        ("ID_BROADCAST_INPUT", ID_BROADCAST_INPUT, 0x00),
+        ("ID_BROADCAST_OUTPUT", ID_BROADCAST_OUTPUT, 0x01),
+        ("ID_BROADCAST_TEMPERATURE", ID_BROADCAST_TEMPERATURE, 0x02),
+        ("ID_BROADCAST_AUX_POWER", ID_BROADCAST_AUX_POWER, 0x03),
+        ("ID_BROADCAST_LIMITS", ID_BROADCAST_LIMITS, 0x04),
+        ("ID_BROADCAST_STATUS", ID_BROADCAST_STATUS, 0x05),
+        (
+            "ID_BROADCAST_POWER_CONNECTOR",
+            ID_BROADCAST_POWER_CONNECTOR,
+            0x06,
+        ),
+        ("ID_COMMAND_MODE", ID_COMMAND_MODE, 0x08),
+        (
+            "ID_COMMAND_MAX_OUTPUT_VOLTAGE",
+            ID_COMMAND_MAX_OUTPUT_VOLTAGE,
+            0x0A,
+        ),
+        (
+            "ID_COMMAND_MAX_INPUT_CURERNT",
+            ID_COMMAND_MAX_INPUT_CURERNT,
+            0x0B,
+        ),
+    ];
+
+    for (name, actual, manual) in expected {
+        assert_eq!(actual, manual, "{} does not match the manual", name);
+    }
+}
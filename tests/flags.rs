@@ -0,0 +1,56 @@
+//! Flag helpers and modes.
+
+use elmar_mppt::*;
+
+#[test]
+fn active_limit_list_holds_each_set_flag() {
+    let flags =
+        LimitFlags::LOCAL_MPPT | LimitFlags::MOSFET_TEMPERATURE | LimitFlags::INPUT_CURRENT_MIN;
+    let mut out = [LimitFlags::empty(); 8];
+
+    let active = flags.active_list(&mut out);
+    assert_eq!(active.len(), 3);
+    assert_eq!(
+        active,
+        [
+            LimitFlags::LOCAL_MPPT,
+            LimitFlags::MOSFET_TEMPERATURE,
+            LimitFlags::INPUT_CURRENT_MIN
+        ]
+    );
+
+    assert!(LimitFlags::empty().active_list(&mut out).is_empty());
+    assert_eq!(LimitFlags::all().active_list(&mut out).len(), 8);
+}
+
+#[test]
+fn mode_predicates() {
+    assert!(Mode::On.is_on());
+    assert!(!Mode::On.is_standby());
+    assert!(Mode::Standby.is_standby());
+    assert!(!Mode::Standby.is_on());
+    assert!(!Mode::Unknown(2).is_on());
+    assert!(!Mode::Unknown(2).is_standby());
+}
+
+#[test]
+fn active_error_flags_skip_the_reserved_bit() {
+    // every bit including the reserved bit 2
+    let flags = ErrorFlags::from_bits_truncate(0xFF);
+    let active: Vec<ErrorFlags> = flags.active().collect();
+
+    assert_eq!(active.len(), 7);
+    assert!(active.iter().all(|flag| flag.bits().count_ones() == 1));
+    assert!(!active.iter().any(|flag| flag.bits() == 1 << 2));
+
+    let set = ErrorFlags::HW_OVERCURRENT | ErrorFlags::MOSFET_OVERHEAT;
+    let descriptions: Vec<&str> = set.active().map(ErrorFlags::describe).collect();
+    assert_eq!(descriptions, ["hardware overcurrent", "MOSFET overheated"]);
+
+    let limits: Vec<&str> = LimitFlags::GLOBAL_MPPT
+        .active()
+        .map(LimitFlags::describe)
+        .collect();
+    assert_eq!(limits, ["global MPPT"]);
+    assert_eq!(ErrorFlags::describe(set), "unknown");
+}
@@ -0,0 +1,50 @@
+//! Deterministic fuzzing of `receive_raw` with pseudo-random payloads.
+
+use elmar_mppt::*;
+
+/// Fixed seed so failures reproduce.
+const SEED: u32 = 0x2545_F491;
+
+/// Payloads tried for every identifier offset and length.
+const ROUNDS: usize = 64;
+
+/// Xorshift generator, good enough to vary the payload bytes.
+struct Xorshift(u32);
+
+impl Xorshift {
+    fn next(&mut self) -> u32 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.0 = x;
+        x
+    }
+}
+
+#[test]
+fn receive_raw_never_panics() {
+    let mut rng = Xorshift(SEED);
+    let mut plain = Mppt::new(ID_BASE).with_reset_on_reboot(true);
+    let mut offset_payload = Mppt::new(ID_BASE).with_payload_offset(1);
+
+    for offset in 0..ID_INC {
+        for dlc in 0..=8 {
+            for _ in 0..ROUNDS {
+                let mut data = [0u8; 8];
+                for byte in data.iter_mut() {
+                    *byte = rng.next() as u8;
+                }
+
+                for mppt in [&mut plain, &mut offset_payload] {
+                    match mppt.receive_raw(ID_BASE + offset, &data[..dlc]) {
+                        Ok(Some(_)) | Ok(None) => {}
+                        Err(Error::ShortPayload { expected, got }) => assert!(got < expected),
+                        Err(Error::UnhandledMessageId(id)) => assert_eq!(id, offset),
+                        Err(e) => panic!("unexpected error {:?} at {:#04x}", e, offset),
+                    }
+                }
+            }
+        }
+    }
+}
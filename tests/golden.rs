@@ -0,0 +1,347 @@
+//! Golden test vectors for decoding the broadcast messages.
+
+mod common;
+
+use common::*;
+use elmar_mppt::*;
+
+type Fields = fn(&Status) -> (Option<f32>, Option<f32>);
+
+/// Offset, payload, decoded fields and the expected values.
+type Vector = (u16, [u8; 8], Fields, (f32, f32));
+
+const FLOAT_VECTORS: &[Vector] = &[
+    (
+        ID_BROADCAST_INPUT,
+        [0x00, 0x00, 0xC8, 0x42, 0x00, 0x00, 0x20, 0x40],
+        |s| (s.input_voltage, s.input_current),
+        (100.0, 2.5),
+    ),
+    (
+        ID_BROADCAST_OUTPUT,
+        [0x00, 0x00, 0x20, 0x43, 0x00, 0x00, 0x80, 0x3F],
+        |s| (s.output_voltage, s.output_current),
+        (160.0, 1.0),
+    ),
+    (
+        ID_BROADCAST_TEMPERATURE,
+        [0x00, 0x00, 0x20, 0x42, 0x00, 0x00, 0xA8, 0xC0],
+        |s| (s.mostfet_temperature, s.controller_temperature),
+        (40.0, -5.25),
+    ),
+    (
+        ID_BROADCAST_AUX_POWER,
+        [0x00, 0x00, 0x40, 0x41, 0x00, 0x00, 0x50, 0x40],
+        |s| (s.rail_12v, s.rail_3v),
+        (12.0, 3.25),
+    ),
+    (
+        ID_BROADCAST_LIMITS,
+        [0x00, 0x00, 0x20, 0x43, 0x00, 0x00, 0x00, 0x41],
+        |s| (s.maximum_output_voltage, s.maximum_input_current),
+        (160.0, 8.0),
+    ),
+    (
+        ID_BROADCAST_POWER_CONNECTOR,
+        [0x00, 0x00, 0x20, 0x43, 0x00, 0x00, 0xC8, 0x41],
+        |s| (s.power_connector_voltage, s.power_connector_temperature),
+        (160.0, 25.0),
+    ),
+];
+
+/// Every float vector decoded at compile time.
+const DECODED: [Option<Status>; FLOAT_VECTORS.len()] = {
+    let mut decoded = [None; FLOAT_VECTORS.len()];
+    let mut i = 0;
+    while i < FLOAT_VECTORS.len() {
+        decoded[i] = Status::decode(FLOAT_VECTORS[i].0, &FLOAT_VECTORS[i].1);
+        i += 1;
+    }
+    decoded
+};
+
+// the input vector is checked while compiling
+const _: () = match DECODED[0] {
+    Some(Status {
+        input_voltage: Some(voltage),
+        input_current: Some(current),
+        ..
+    }) => assert!(voltage == 100.0 && current == 2.5),
+    _ => panic!("input vector did not decode"),
+};
+
+/// Payload of the status message vector.
+const STATUS_PAYLOAD: [u8; 8] = [0x01, 0x02, 0x03, 0x50, 0x41, 0x01, 0x00, 0x2A];
+
+/// Payloads of the messages without a float vector.
+const OTHER_VECTORS: &[(u16, [u8; 8])] = &[
+    (ID_BROADCAST_STATUS, STATUS_PAYLOAD),
+    (ID_COMMAND_MODE, [0x01, 0, 0, 0, 0, 0, 0, 0]),
+    (
+        ID_COMMAND_MAX_OUTPUT_VOLTAGE,
+        [0x00, 0x00, 0x20, 0x43, 0, 0, 0, 0],
+    ),
+    (
+        ID_COMMAND_MAX_INPUT_CURERNT,
+        [0x00, 0x00, 0x00, 0x41, 0, 0, 0, 0],
+    ),
+    // 86400 s and 1250.5 Wh
+    #[cfg(feature = "vendor-extended-status")]
+    (
+        ID_BROADCAST_EXTENDED_STATUS,
+        [0x80, 0x51, 0x01, 0x00, 0x00, 0x50, 0x9C, 0x44],
+    ),
+];
+
+// the status vector is checked while compiling
+const _: () = match Status::decode(ID_BROADCAST_STATUS, &STATUS_PAYLOAD) {
+    Some(Status {
+        can_rx_error_count: Some(1),
+        raw_error_byte: Some(0x50),
+        raw_limit_byte: Some(0x41),
+        mode: Some(Mode::On),
+        test_counter: Some(42),
+        ..
+    }) => {}
+    _ => panic!("status vector did not decode"),
+};
+
+#[test]
+fn const_decoded_vectors() {
+    for ((offset, data, fields, (lower, upper)), decoded) in FLOAT_VECTORS.iter().zip(DECODED) {
+        let decoded = decoded.unwrap();
+        assert_eq!(fields(&decoded), (Some(*lower), Some(*upper)));
+
+        // the same as decoding at run time
+        let mut mppt = Mppt::new(ID_BASE);
+        receive(&mut mppt, *offset, *data);
+        assert_eq!(decoded, mppt.status(), "offset {:#04x}", offset);
+    }
+
+    assert_eq!(Status::decode(0x09, &[0; 8]), None);
+}
+
+#[test]
+fn const_decode_matches_receive_for_every_message() {
+    let vectors = FLOAT_VECTORS
+        .iter()
+        .map(|(offset, data, _, _)| (*offset, *data))
+        .chain(OTHER_VECTORS.iter().copied());
+
+    let mut covered = Vec::new();
+    for (offset, data) in vectors {
+        let mut mppt = Mppt::new(ID_BASE);
+        receive(&mut mppt, offset, data);
+
+        let decoded = Status::decode(offset, &data);
+        assert_eq!(decoded, Some(mppt.status()), "offset {:#04x}", offset);
+        covered.push(offset);
+    }
+
+    let mppt = Mppt::new(ID_BASE);
+    for offset in 0..=ID_COMMAND_MAX_INPUT_CURERNT {
+        if mppt.is_known_message(&frame(offset, [0; 8])) {
+            assert!(covered.contains(&offset), "offset {:#04x}", offset);
+        }
+    }
+}
+
+#[test]
+fn float_vectors() {
+    for (offset, data, fields, (lower, upper)) in FLOAT_VECTORS {
+        let mut mppt = Mppt::new(ID_BASE);
+        receive(&mut mppt, *offset, *data);
+
+        assert_eq!(
+            fields(&mppt.status()),
+            (Some(*lower), Some(*upper)),
+            "offset {:#04x}",
+            offset
+        );
+    }
+}
+
+#[test]
+fn status_vector() {
+    let mut mppt = Mppt::new(ID_BASE);
+    receive(&mut mppt, ID_BROADCAST_STATUS, STATUS_PAYLOAD);
+    let status = mppt.status();
+
+    assert_eq!(status.can_rx_error_count, Some(1));
+    assert_eq!(status.can_tx_error_count, Some(2));
+    assert_eq!(status.can_tx_overflow_count, Some(3));
+    assert_eq!(
+        status.error_flags,
+        Some(ErrorFlags::BATTERY_FULL | ErrorFlags::MOSFET_OVERHEAT)
+    );
+    assert_eq!(
+        status.limit_flags,
+        Some(LimitFlags::GLOBAL_MPPT | LimitFlags::INPUT_CURRENT_MAX)
+    );
+    assert!(status.mode == Some(Mode::On));
+    assert_eq!(status.test_counter, Some(42));
+}
+
+#[test]
+fn golden_status_is_populated() {
+    let status = golden();
+
+    let floats = [
+        status.input_voltage,
+        status.input_current,
+        status.output_voltage,
+        status.output_current,
+        status.mostfet_temperature,
+        status.controller_temperature,
+        status.rail_12v,
+        status.rail_3v,
+        status.maximum_output_voltage,
+        status.maximum_input_current,
+        status.power_connector_voltage,
+        status.power_connector_temperature,
+        status.commanded_max_output_voltage,
+        status.commanded_max_input_current,
+    ];
+    let bytes = [
+        status.can_rx_error_count,
+        status.can_tx_error_count,
+        status.can_tx_overflow_count,
+        status.raw_error_byte,
+        status.raw_limit_byte,
+        status.test_counter,
+    ];
+
+    assert!(floats.iter().all(Option::is_some));
+    assert!(bytes.iter().all(Option::is_some));
+    assert!(status.error_flags.is_some() && status.limit_flags.is_some());
+    assert!(status.mode.is_some() && status.commanded_mode.is_some());
+}
+
+#[test]
+fn golden_status_wire_round_trip() {
+    let status = golden();
+    let decoded = Status::from_bytes(&status.to_bytes()).unwrap();
+
+    // vendor fields are not part of the encoding
+    let expected = Status {
+        #[cfg(feature = "vendor-extended-status")]
+        uptime_s: None,
+        #[cfg(feature = "vendor-extended-status")]
+        lifetime_energy_wh: None,
+        ..status
+    };
+    assert!(decoded == expected);
+}
+
+#[test]
+fn short_limits_frame() {
+    let mut mppt = Mppt::new(ID_BASE);
+    let id = ID_BASE + ID_BROADCAST_LIMITS;
+
+    assert_eq!(
+        mppt.receive_raw(id, &[0x00, 0x00, 0x20, 0x43]),
+        Ok(Some(Broadcast::Limits))
+    );
+    assert_eq!(mppt.snapshot().maximum_output_voltage, Some(160.0));
+    assert_eq!(mppt.snapshot().maximum_input_current, None);
+
+    // a known upper field is kept when a later frame omits it
+    receive(
+        &mut mppt,
+        ID_BROADCAST_LIMITS,
+        [0x00, 0x00, 0x20, 0x43, 0x00, 0x00, 0x00, 0x41],
+    );
+    mppt.receive_raw(id, &[0x00, 0x00, 0x10, 0x43]).unwrap();
+    assert_eq!(mppt.snapshot().maximum_output_voltage, Some(144.0));
+    assert_eq!(mppt.snapshot().maximum_input_current, Some(8.0));
+}
+
+#[test]
+fn centidegree_temperatures() {
+    let mut mppt = Mppt::new(ID_BASE).with_temperature_format(TemperatureFormat::Centidegrees);
+
+    let mut data = [0u8; 8];
+    data[..2].copy_from_slice(&(-525i16).to_le_bytes());
+    data[4..6].copy_from_slice(&4000i16.to_le_bytes());
+    receive(&mut mppt, ID_BROADCAST_TEMPERATURE, data);
+
+    assert_eq!(mppt.snapshot().mostfet_temperature, Some(-5.25));
+    assert_eq!(mppt.snapshot().controller_temperature, Some(40.0));
+}
+
+#[test]
+fn scaling_profile_corrects_decoded_values() {
+    let scaling = ScalingProfile {
+        mostfet_temperature: Linear {
+            scale: 1.0,
+            offset: 0.5,
+        },
+        input_voltage: Linear {
+            scale: 2.0,
+            offset: 0.0,
+        },
+        ..ScalingProfile::identity()
+    };
+    let mut mppt = Mppt::new(ID_BASE).with_scaling_profile(scaling);
+
+    receive(&mut mppt, ID_BROADCAST_TEMPERATURE, FLOAT_VECTORS[2].1);
+    receive(&mut mppt, ID_BROADCAST_INPUT, FLOAT_VECTORS[0].1);
+
+    let status = mppt.snapshot();
+    assert_eq!(status.mostfet_temperature, Some(40.5));
+    assert_eq!(status.controller_temperature, Some(-5.25));
+    assert_eq!(status.input_voltage, Some(200.0));
+    assert_eq!(status.input_current, Some(2.5));
+}
+
+#[cfg(feature = "vendor-extended-status")]
+#[test]
+fn vendor_extended_status() {
+    let mut mppt = Mppt::new(ID_BASE);
+
+    let mut data = [0u8; 8];
+    data[..4].copy_from_slice(&86_400u32.to_le_bytes());
+    data[4..].copy_from_slice(&1250.5f32.to_le_bytes());
+    receive(&mut mppt, ID_BROADCAST_EXTENDED_STATUS, data);
+
+    assert_eq!(mppt.snapshot().uptime_s, Some(86_400));
+    assert_eq!(mppt.snapshot().lifetime_energy_wh, Some(1250.5));
+}
+
+#[test]
+fn payload_offset_skips_a_prefix() {
+    let mut mppt = Mppt::new(ID_BASE).with_payload_offset(1);
+
+    // a gateway prepended a length byte, pushing the upper float out
+    let (_, input, _, _) = FLOAT_VECTORS[0];
+    let mut data = [0x08u8; 8];
+    data[1..].copy_from_slice(&input[..7]);
+    receive(&mut mppt, ID_BROADCAST_INPUT, data);
+
+    assert_eq!(mppt.snapshot().input_voltage, Some(100.0));
+    assert_eq!(mppt.snapshot().input_current, None);
+
+    // with room for a full payload every value decodes
+    let mut data = [0x00u8; 9];
+    data[1..].copy_from_slice(&FLOAT_VECTORS[3].1);
+    mppt.receive_raw(ID_BASE + ID_BROADCAST_AUX_POWER, &data)
+        .unwrap();
+    assert_eq!(mppt.snapshot().rail_12v, Some(12.0));
+    assert_eq!(mppt.snapshot().rail_3v, Some(3.25));
+}
+
+#[test]
+fn milliamp_currents_are_stored_in_amps() {
+    let mut mppt = Mppt::new(ID_BASE).with_input_current_unit(CurrentUnit::Milliamps);
+
+    let mut data = [0u8; 8];
+    data[..4].copy_from_slice(&100.0f32.to_le_bytes());
+    data[4..].copy_from_slice(&2500i32.to_le_bytes());
+    receive(&mut mppt, ID_BROADCAST_INPUT, data);
+    // the output current keeps the documented float encoding
+    receive(&mut mppt, ID_BROADCAST_OUTPUT, FLOAT_VECTORS[1].1);
+
+    assert_eq!(mppt.snapshot().input_voltage, Some(100.0));
+    assert_eq!(mppt.snapshot().input_current, Some(2.5));
+    assert_eq!(mppt.snapshot().output_current, Some(1.0));
+}
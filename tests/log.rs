@@ -0,0 +1,60 @@
+//! The compact status encoding and log records.
+
+use elmar_mppt::*;
+
+fn presence(buf: &[u8; STATUS_WIRE_LEN]) -> u32 {
+    u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]])
+}
+
+#[test]
+fn partial_status_round_trip() {
+    let status = Status {
+        input_voltage: Some(100.0),
+        output_current: Some(-0.0),
+        mode: Some(Mode::On),
+        ..Default::default()
+    };
+
+    let buf = status.to_bytes();
+    // input voltage, output current and mode (after the 14 float slots)
+    assert_eq!(presence(&buf), 1 << 0 | 1 << 3 | 1 << (14 + 5));
+    assert_eq!(Status::from_bytes(&buf), Ok(status));
+
+    // values without their presence bit are not decoded
+    let mut buf = Status::default().to_bytes();
+    buf[4..8].copy_from_slice(&100.0f32.to_le_bytes());
+    buf[0] = 1 << 2;
+    buf[12..16].copy_from_slice(&48.0f32.to_le_bytes());
+    let decoded = Status::from_bytes(&buf).unwrap();
+    assert_eq!(decoded.input_voltage, None);
+    assert_eq!(
+        decoded,
+        Status {
+            output_voltage: Some(48.0),
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn log_record_round_trip() {
+    let status = Status {
+        output_voltage: Some(160.0),
+        error_flags: Some(ErrorFlags::BATTERY_FULL),
+        // with the reserved bit 2 set
+        raw_error_byte: Some(0x14),
+        test_counter: Some(42),
+        ..Default::default()
+    };
+
+    let record = status.encode_log(513, 0xDEAD_BEEF);
+    assert_eq!(record[0], LOG_VERSION);
+    assert_eq!(Status::decode_log(&record), Ok((513, 0xDEAD_BEEF, status)));
+
+    let mut old = record;
+    old[0] = LOG_VERSION - 1;
+    assert_eq!(
+        Status::decode_log(&old),
+        Err(Error::UnsupportedLogVersion(LOG_VERSION - 1))
+    );
+}
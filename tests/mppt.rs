@@ -0,0 +1,150 @@
+//! Device configuration and the analysis helpers on `Mppt`.
+
+mod common;
+
+use common::*;
+use elmar_mppt::*;
+
+#[test]
+fn name_is_included_in_diagnostics() {
+    let mppt = Mppt::new(ID_BASE).with_name("MPPT 3");
+
+    assert_eq!(mppt.name(), Some("MPPT 3"));
+    assert!(format!("{:?}", mppt).contains("\"MPPT 3\""));
+    assert_eq!(Mppt::new(ID_BASE).name(), None);
+}
+
+#[test]
+fn thermal_runaway_estimate_at_a_steady_loss() {
+    let mut mppt = Mppt::new(ID_BASE);
+    assert_eq!(mppt.thermal_runway_estimate(50.0, 80.0), None);
+
+    // 200 W in and 190 W out, dissipating 10 W into 50 J/°C at 40 °C
+    receive(&mut mppt, ID_BROADCAST_INPUT, floats(100.0, 2.0));
+    receive(&mut mppt, ID_BROADCAST_OUTPUT, floats(95.0, 2.0));
+    receive(&mut mppt, ID_BROADCAST_TEMPERATURE, floats(40.0, 35.0));
+
+    assert_eq!(mppt.thermal_runway_estimate(50.0, 80.0), Some(200));
+    // a larger thermal mass takes longer to heat
+    assert_eq!(mppt.thermal_runway_estimate(100.0, 80.0), Some(400));
+    assert_eq!(mppt.thermal_runway_estimate(50.0, 30.0), Some(0));
+    assert_eq!(mppt.thermal_runway_estimate(0.0, 80.0), None);
+}
+
+#[test]
+fn thermal_derating_at_cool_and_hot_temperatures() {
+    let mut mppt = Mppt::new(ID_BASE);
+    assert_eq!(mppt.thermal_derated_max_input(10.0), None);
+
+    receive(&mut mppt, ID_BROADCAST_TEMPERATURE, floats(40.0, 35.0));
+    assert_eq!(mppt.thermal_derated_max_input(10.0), Some(10.0));
+
+    // halfway between the nominal maximum and the derate limit
+    receive(&mut mppt, ID_BROADCAST_TEMPERATURE, floats(90.0, 35.0));
+    assert_eq!(mppt.thermal_derated_max_input(10.0), Some(5.0));
+
+    receive(&mut mppt, ID_BROADCAST_TEMPERATURE, floats(110.0, 35.0));
+    assert_eq!(mppt.thermal_derated_max_input(10.0), Some(0.0));
+}
+
+#[test]
+fn output_voltage_tracking_error_below_and_at_the_ceiling() {
+    let mut mppt = Mppt::new(ID_BASE);
+    receive(&mut mppt, ID_BROADCAST_LIMITS, floats(160.0, 8.0));
+    assert_eq!(mppt.output_voltage_tracking_error(), None);
+
+    receive(&mut mppt, ID_BROADCAST_OUTPUT, floats(150.0, 1.0));
+    assert_eq!(mppt.output_voltage_tracking_error(), Some(10.0));
+
+    receive(&mut mppt, ID_BROADCAST_OUTPUT, floats(160.0, 1.0));
+    assert_eq!(mppt.output_voltage_tracking_error(), Some(0.0));
+}
+
+#[test]
+fn output_voltage_stddev_of_a_known_sample_set() {
+    let mut mppt = Mppt::new(ID_BASE);
+    receive(&mut mppt, ID_BROADCAST_OUTPUT, floats(150.0, 1.0));
+    assert_eq!(mppt.output_voltage_stddev(), None);
+
+    // deviations with a population standard deviation of exactly 2 V
+    for deviation in [4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0] {
+        receive(
+            &mut mppt,
+            ID_BROADCAST_OUTPUT,
+            floats(148.0 + deviation, 1.0),
+        );
+    }
+    // non-finite readings are ignored
+    receive(&mut mppt, ID_BROADCAST_OUTPUT, floats(f32::NAN, 1.0));
+
+    let stddev = mppt.output_voltage_stddev().unwrap();
+    assert!((stddev - 2.0).abs() < 1e-4, "{}", stddev);
+
+    mppt.reset_status();
+    assert_eq!(mppt.output_voltage_stddev(), None);
+}
+
+#[test]
+fn old_output_voltage_excursions_decay_out() {
+    let mut mppt = Mppt::new(ID_BASE);
+    for voltage in [100.0, 160.0, 100.0, 160.0] {
+        receive(&mut mppt, ID_BROADCAST_OUTPUT, floats(voltage, 1.0));
+    }
+    assert!(mppt.output_voltage_stddev().unwrap() > 25.0);
+
+    // a steady output for a few windows
+    for _ in 0..OUTPUT_VOLTAGE_STDDEV_WINDOW * 8 {
+        receive(&mut mppt, ID_BROADCAST_OUTPUT, floats(150.0, 1.0));
+    }
+    let stddev = mppt.output_voltage_stddev().unwrap();
+    assert!(stddev < 1.0, "{}", stddev);
+}
+
+#[test]
+fn switch_positions_set_the_base_id() {
+    assert_eq!(Mppt::from_switch(0).unwrap().base_id(), ID_BASE);
+    assert_eq!(
+        Mppt::from_switch(15).unwrap().base_id(),
+        ID_BASE + 15 * ID_INC
+    );
+    assert!(Mppt::from_switch(16).is_none());
+
+    // re-homing keeps the received status
+    let mut mppt = Mppt::from_switch(1).unwrap();
+    receive(&mut mppt, ID_BROADCAST_INPUT, floats(100.0, 2.0));
+    assert_eq!(mppt.set_base_id(ID_BASE + 3 * ID_INC), Ok(()));
+    assert_eq!(mppt.base_id(), ID_BASE + 3 * ID_INC);
+    assert_eq!(mppt.snapshot().input_voltage, Some(100.0));
+}
+
+#[test]
+fn alternating_limits_are_an_oscillation() {
+    let current = LimitFlags::INPUT_CURRENT_MAX.bits();
+    let voltage = LimitFlags::OUTPUT_VOLTAGE_MAX.bits();
+    let mppt_with = |limits: &[u8]| {
+        let mut mppt = Mppt::new(ID_BASE);
+        for (counter, &limit) in limits.iter().enumerate() {
+            let data = [0, 0, 0, 0, limit, 1, 0, counter as u8];
+            receive(&mut mppt, ID_BROADCAST_STATUS, data);
+        }
+        mppt
+    };
+
+    let alternating = mppt_with(&[current, voltage, current, voltage]);
+    assert!(alternating.limit_mode_oscillating(4));
+    // older switches fall outside a short window
+    assert!(!alternating.limit_mode_oscillating(3));
+
+    // frames tracking the maximum power point in between do not hide it
+    let mppt = LimitFlags::GLOBAL_MPPT.bits();
+    let interleaved = mppt_with(&[current, mppt, voltage, mppt, current, voltage]);
+    assert!(interleaved.limit_mode_oscillating(LIMIT_HISTORY_LEN));
+
+    // a single move to the other limit and back is not an oscillation
+    let settled = mppt_with(&[current, current, voltage, voltage, current]);
+    assert!(!settled.limit_mode_oscillating(LIMIT_HISTORY_LEN));
+
+    let mut reset = alternating;
+    reset.reset_status();
+    assert!(!reset.limit_mode_oscillating(LIMIT_HISTORY_LEN));
+}
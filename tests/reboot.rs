@@ -0,0 +1,77 @@
+//! Reboot detection from the status frame test counter.
+
+mod common;
+
+use common::*;
+use elmar_mppt::*;
+
+fn status_frame(mppt: &mut Mppt, counter: u8) {
+    receive(mppt, ID_BROADCAST_STATUS, status_payload(0, 1, counter));
+}
+
+#[test]
+fn counter_jumping_back_to_zero_is_a_reboot() {
+    let mut mppt = Mppt::new(ID_BASE);
+
+    for counter in [10, 11, 12] {
+        status_frame(&mut mppt, counter);
+    }
+    assert!(!mppt.detected_reboot());
+
+    status_frame(&mut mppt, 0);
+    assert!(mppt.detected_reboot());
+    // reading clears the flag
+    assert!(!mppt.detected_reboot());
+}
+
+#[test]
+fn reset_to_zero_from_a_high_counter_is_a_reboot() {
+    let mut mppt = Mppt::new(ID_BASE);
+
+    status_frame(&mut mppt, 200);
+    status_frame(&mut mppt, 0);
+    assert!(mppt.detected_reboot());
+
+    // well past the wrap allowance
+    status_frame(&mut mppt, 250);
+    status_frame(&mut mppt, 10);
+    assert!(mppt.detected_reboot());
+}
+
+#[test]
+fn counter_wrapping_is_not_a_reboot() {
+    let mut mppt = Mppt::new(ID_BASE);
+
+    status_frame(&mut mppt, 255);
+    status_frame(&mut mppt, 0);
+    assert!(!mppt.detected_reboot());
+
+    // frames lost across the wrap
+    status_frame(&mut mppt, 254);
+    status_frame(&mut mppt, 2);
+    assert!(!mppt.detected_reboot());
+}
+
+#[test]
+fn throttled_status_frames_keep_the_counter_current() {
+    let mut mppt = Mppt::new(ID_BASE)
+        .with_reset_on_reboot(true)
+        .with_min_status_interval(1000);
+    let input = frame(ID_BROADCAST_INPUT, floats(100.0, 2.5));
+    mppt.receive_at(&input, 0).unwrap();
+
+    // one status frame every 200 ms wraps the counter twice, with only
+    // every fifth frame applied
+    for i in 0..600u32 {
+        let status = frame(ID_BROADCAST_STATUS, status_payload(0, 1, i as u8));
+        mppt.receive_at(&status, i * 200).unwrap();
+    }
+    assert!(!mppt.detected_reboot());
+    assert_eq!(mppt.snapshot().input_voltage, Some(100.0));
+
+    // a throttled frame still detects a real reboot
+    let status = frame(ID_BROADCAST_STATUS, status_payload(0, 1, 0));
+    assert_eq!(mppt.receive_at(&status, 599 * 200 + 100), Ok(None));
+    assert!(mppt.detected_reboot());
+    assert_eq!(mppt.snapshot().input_voltage, None);
+}
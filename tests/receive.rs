@@ -0,0 +1,375 @@
+//! The `receive` contract: which frames are decoded, ignored or rejected.
+
+mod common;
+
+use bxcan::{ExtendedId, Frame, Id, StandardId};
+use common::*;
+use elmar_mppt::*;
+
+const EXTENDED_BASE: u32 = 0x1800_0600;
+
+fn extended(id: u32, data: &[u8]) -> Frame {
+    Frame::new_data(ExtendedId::new(id).unwrap(), data_of(data))
+}
+
+fn data_of(data: &[u8]) -> bxcan::Data {
+    bxcan::Data::new(data).unwrap()
+}
+
+#[test]
+fn extended_input_broadcast_is_decoded() {
+    let input = floats(100.0, 2.5);
+    let mut mppt = Mppt::new_extended(EXTENDED_BASE).unwrap();
+
+    let frame = extended(EXTENDED_BASE + ID_BROADCAST_INPUT as u32, &input);
+    assert_eq!(mppt.receive(&frame), Ok(Some(Broadcast::Input)));
+    assert_eq!(mppt.snapshot().input_voltage, Some(100.0));
+    assert_eq!(mppt.snapshot().input_current, Some(2.5));
+
+    assert_eq!(
+        mppt.receive_raw_extended(EXTENDED_BASE + ID_BROADCAST_INPUT as u32, &input),
+        Ok(Some(Broadcast::Input))
+    );
+}
+
+#[test]
+fn extended_device_ignores_standard_frames() {
+    let input = floats(100.0, 2.5);
+    let mut mppt = Mppt::new_extended(ID_BASE as u32).unwrap();
+
+    assert_eq!(mppt.receive(&frame(ID_BROADCAST_INPUT, input)), Ok(None));
+    assert_eq!(mppt.receive_raw(ID_BASE, &input), Ok(None));
+    assert_eq!(mppt.snapshot(), Status::default());
+}
+
+#[test]
+fn extended_device_commands_use_extended_ids() {
+    let mut mppt = Mppt::new_extended(EXTENDED_BASE).unwrap();
+    let id = |offset: u16| Id::Extended(ExtendedId::new(EXTENDED_BASE + offset as u32).unwrap());
+
+    assert_eq!(mppt.set_mode(Mode::On).id(), id(ID_COMMAND_MODE));
+    assert_eq!(
+        mppt.command(Command::MaxInputCurrent(8.0)).unwrap().id(),
+        id(ID_COMMAND_MAX_INPUT_CURERNT)
+    );
+
+    let standard = Mppt::new(ID_BASE).set_mode(Mode::On);
+    assert_eq!(
+        standard.id(),
+        Id::Standard(StandardId::new(ID_BASE + ID_COMMAND_MODE).unwrap())
+    );
+}
+
+#[test]
+fn base_ids_without_room_for_commands_are_rejected() {
+    assert!(Mppt::new_extended(0x2000_0000).is_none());
+    assert!(Mppt::new_extended(u32::MAX).is_none());
+    assert!(Mppt::new_extended(0x1FFF_FFF4).is_some());
+
+    let mut mppt = Mppt::new(ID_BASE);
+    assert_eq!(mppt.set_base_id(0x7F8), Err(Error::InvalidBaseId(0x7F8)));
+    assert_eq!(mppt.follow(0x7F8), Err(Error::InvalidBaseId(0x7F8)));
+    assert_eq!(mppt.base_id(), ID_BASE);
+    assert_eq!(mppt.set_base_id(0x7F4), Ok(()));
+    let frame = mppt.set_maximum_input_current(8.0).unwrap();
+    assert_eq!(frame.id(), Id::Standard(StandardId::MAX));
+}
+
+#[test]
+fn base_id_setters_keep_the_identifier_class() {
+    let mut standard = Mppt::new(ID_BASE);
+    assert_eq!(
+        standard.set_extended_base_id(EXTENDED_BASE),
+        Err(Error::WrongIdClass)
+    );
+    assert_eq!(standard.id_kind(), IdKind::Standard);
+
+    let mut extended = Mppt::new_extended(EXTENDED_BASE).unwrap();
+    assert_eq!(extended.set_base_id(ID_BASE), Err(Error::WrongIdClass));
+    assert_eq!(extended.follow_extended(EXTENDED_BASE + 0x10), Ok(()));
+    assert_eq!(extended.extended_base_id(), EXTENDED_BASE + 0x10);
+    assert_eq!(extended.id_kind(), IdKind::Extended);
+}
+
+#[test]
+fn frames_for_other_devices_are_ignored() {
+    let input = floats(100.0, 2.5);
+    let mut mppt = Mppt::new(ID_BASE + ID_INC);
+
+    let below = Frame::new_data(StandardId::new(ID_BASE).unwrap(), input);
+    let above = Frame::new_data(
+        StandardId::new(ID_BASE + ID_INC + ID_COMMAND_MAX_INPUT_CURERNT + 1).unwrap(),
+        input,
+    );
+    let other_class = extended((ID_BASE + ID_INC) as u32, &input);
+
+    for frame in [below, above, other_class] {
+        assert!(!mppt.is_for_device(&frame));
+        assert_eq!(mppt.receive(&frame), Ok(None));
+    }
+    assert_eq!(mppt.snapshot(), Status::default());
+}
+
+#[test]
+fn is_for_device_covers_the_whole_block() {
+    let mppt = Mppt::new(ID_BASE);
+
+    for offset in 0..=ID_COMMAND_MAX_INPUT_CURERNT {
+        assert!(
+            mppt.is_for_device(&frame(offset, [])),
+            "offset {:#04x}",
+            offset
+        );
+    }
+    assert!(!mppt.is_for_device(&frame(ID_INC, [])));
+}
+
+#[derive(Default)]
+struct Recorder {
+    updates: Vec<(Broadcast, Option<f32>)>,
+}
+
+impl StatusSink for Recorder {
+    fn on_update(&mut self, group: Broadcast, status: &Status) {
+        self.updates.push((group, status.input_voltage));
+    }
+}
+
+#[test]
+fn receive_into_notifies_the_sink_of_decoded_groups() {
+    let input = floats(100.0, 2.5);
+    let mut mppt = Mppt::new(ID_BASE);
+    let mut sink = Recorder::default();
+
+    assert_eq!(
+        mppt.receive_into(&frame(ID_BROADCAST_INPUT, input), &mut sink),
+        Ok(Some(Broadcast::Input))
+    );
+    assert_eq!(
+        mppt.receive_into(&frame(ID_BROADCAST_OUTPUT, input), &mut sink),
+        Ok(Some(Broadcast::Output))
+    );
+
+    // Unknown offsets and short payloads do not notify the sink.
+    assert_eq!(mppt.receive_into(&frame(0x0F, input), &mut sink), Ok(None));
+    assert!(mppt
+        .receive_into(&frame(ID_BROADCAST_STATUS, &input[..4]), &mut sink)
+        .is_err());
+
+    assert_eq!(
+        sink.updates,
+        [
+            (Broadcast::Input, Some(100.0)),
+            (Broadcast::Output, Some(100.0))
+        ]
+    );
+}
+
+#[test]
+fn status_apply_matches_receive_for_each_message() {
+    let input = floats(100.0, 2.5);
+
+    for &(offset, kind) in MESSAGES {
+        let mut mppt = Mppt::new(ID_BASE);
+        assert_eq!(mppt.receive_raw(ID_BASE + offset, &input), Ok(Some(kind)));
+
+        let mut status = Status::default();
+        assert_eq!(status.apply(kind, &input), Ok(()));
+        assert_eq!(status, mppt.snapshot(), "{kind:?}");
+    }
+
+    let mut status = Status::default();
+    status.apply(Broadcast::Input, &input).unwrap();
+    assert_eq!(status.input_voltage, Some(100.0));
+    assert_eq!(status.input_current, Some(2.5));
+}
+
+#[test]
+fn short_status_frame_is_rejected() {
+    let mut mppt = Mppt::new(ID_BASE);
+
+    assert_eq!(
+        mppt.receive(&frame(ID_BROADCAST_STATUS, [0x01, 0x02, 0x03, 0x04])),
+        Err(Error::ShortPayload {
+            expected: 8,
+            got: 4
+        })
+    );
+    assert_eq!(mppt.snapshot(), Status::default());
+}
+
+/// Status broadcast with the given mode byte and every other byte set.
+fn status_with_mode(mode: u8) -> [u8; 8] {
+    [0x01, 0x02, 0x03, 0x00, 0x00, mode, 0x00, 0x10]
+}
+
+#[test]
+fn unknown_mode_keeps_the_rest_of_the_status() {
+    let mut mppt = Mppt::new(ID_BASE);
+
+    assert_eq!(
+        mppt.receive(&frame(ID_BROADCAST_STATUS, status_with_mode(2))),
+        Ok(Some(Broadcast::StatusFrame))
+    );
+
+    let status = mppt.snapshot();
+    assert_eq!(status.mode, Some(Mode::Unknown(2)));
+    assert_eq!(status.can_rx_error_count, Some(0x01));
+    assert_eq!(status.can_tx_error_count, Some(0x02));
+    assert_eq!(status.can_tx_overflow_count, Some(0x03));
+    assert_eq!(status.test_counter, Some(0x10));
+    // fields of other messages are untouched
+    assert_eq!(status.input_voltage, None);
+    assert_eq!(status.maximum_input_current, None);
+}
+
+#[test]
+fn receive_errors_can_be_matched() {
+    let input = floats(100.0, 2.5);
+    let mut mppt = Mppt::new(ID_BASE);
+    let remote = Frame::new_remote(StandardId::new(ID_BASE).unwrap(), 8);
+
+    match mppt.receive(&remote) {
+        Err(Error::NotADataFrame) => {}
+        other => panic!("unexpected {:?}", other),
+    }
+    match mppt.receive(&frame(0x09, input)) {
+        Err(Error::UnhandledMessageId(offset)) => assert_eq!(offset, 0x09),
+        other => panic!("unexpected {:?}", other),
+    }
+    match mppt.receive(&frame(ID_BROADCAST_LIMITS, [0x00, 0x00])) {
+        Err(Error::ShortPayload { expected, got }) => assert_eq!((expected, got), (4, 2)),
+        other => panic!("unexpected {:?}", other),
+    }
+    assert_eq!(mppt.snapshot(), Status::default());
+}
+
+#[test]
+fn receive_reports_each_decoded_group() {
+    let mut mppt = Mppt::new(ID_BASE);
+    let input = floats(100.0, 2.5);
+
+    for &(offset, group) in MESSAGES
+        .iter()
+        .filter(|(_, group)| !group.is_command_echo())
+    {
+        assert_eq!(mppt.receive(&frame(offset, input)), Ok(Some(group)));
+    }
+
+    // frames that are not for the device report no group
+    let below = Frame::new_data(StandardId::new(ID_BASE - 1).unwrap(), input);
+    let above = frame(ID_COMMAND_MAX_INPUT_CURERNT + 1, input);
+    let other_class = extended((ID_BASE + ID_BROADCAST_INPUT) as u32, &input);
+    for frame in [below, above, other_class] {
+        assert_eq!(mppt.receive(&frame), Ok(None));
+    }
+}
+
+#[test]
+fn receive_raw_decodes_byte_arrays() {
+    let input = floats(100.0, 2.5);
+    let mut mppt = Mppt::new(ID_BASE);
+
+    assert_eq!(
+        mppt.receive_raw(ID_BASE + ID_BROADCAST_INPUT, &input),
+        Ok(Some(Broadcast::Input))
+    );
+    assert_eq!(mppt.snapshot().input_voltage, Some(100.0));
+    assert_eq!(mppt.snapshot().input_current, Some(2.5));
+
+    assert_eq!(
+        mppt.receive_raw(ID_BASE + ID_BROADCAST_STATUS, &[0x00, 0x00, 0x00, 0x00]),
+        Err(Error::ShortPayload {
+            expected: 8,
+            got: 4
+        })
+    );
+    assert_eq!(mppt.receive_raw(ID_BASE + ID_INC, &input), Ok(None));
+
+    // same result as the equivalent frame
+    let mut framed = Mppt::new(ID_BASE);
+    framed.receive(&frame(ID_BROADCAST_INPUT, input)).unwrap();
+    assert_eq!(framed.snapshot(), mppt.snapshot());
+}
+
+#[test]
+fn future_mode_bytes_are_unknown_modes() {
+    let mut mppt = Mppt::new(ID_BASE);
+
+    assert_eq!(
+        mppt.receive(&frame(ID_BROADCAST_STATUS, status_with_mode(3))),
+        Ok(Some(Broadcast::StatusFrame))
+    );
+    assert_eq!(mppt.snapshot().mode, Some(Mode::Unknown(3)));
+
+    mppt.receive(&frame(ID_BROADCAST_STATUS, status_with_mode(1)))
+        .unwrap();
+    assert_eq!(mppt.snapshot().mode, Some(Mode::On));
+}
+
+#[test]
+fn known_messages_are_recognized() {
+    let input = floats(100.0, 2.5);
+    let mppt = Mppt::new(ID_BASE);
+
+    assert!(mppt.is_known_message(&frame(ID_BROADCAST_INPUT, input)));
+    assert!(mppt.is_known_message(&frame(ID_COMMAND_MODE, [1])));
+    assert!(!mppt.is_known_message(&frame(0x09, input)));
+    #[cfg(not(feature = "vendor-extended-status"))]
+    assert!(!mppt.is_known_message(&frame(0x07, input)));
+    // known offsets of another device
+    assert!(!mppt.is_known_message(&frame(ID_INC + ID_BROADCAST_INPUT, input)));
+}
+
+#[test]
+fn peek_decodes_each_message_without_updating() {
+    let input = floats(100.0, 2.5);
+    let mut mppt = Mppt::new(ID_BASE);
+    mppt.receive(&frame(ID_BROADCAST_OUTPUT, input)).unwrap();
+    let before = mppt.snapshot();
+
+    for &(offset, kind) in MESSAGES {
+        let (group, decoded) = mppt.peek(&frame(offset, input)).unwrap().unwrap();
+        assert_eq!(group, kind);
+
+        // the same values as decoding into an empty status
+        let mut expected = Status::default();
+        expected.apply(kind, &input).unwrap();
+        assert_eq!(decoded, expected, "{kind:?}");
+    }
+
+    let (_, peeked) = mppt
+        .peek(&frame(ID_BROADCAST_INPUT, input))
+        .unwrap()
+        .unwrap();
+    assert_eq!(peeked.input_voltage, Some(100.0));
+    assert_eq!(peeked.output_voltage, None);
+
+    assert_eq!(mppt.peek(&frame(ID_INC, input)), Ok(None));
+    assert_eq!(
+        mppt.peek(&frame(ID_BROADCAST_STATUS, &input[..4])),
+        Err(Error::ShortPayload {
+            expected: 8,
+            got: 4
+        })
+    );
+    assert_eq!(mppt.snapshot(), before);
+}
+
+#[test]
+fn raw_status_bytes_keep_reserved_bits() {
+    let mut mppt = Mppt::new(ID_BASE);
+
+    // reserved error bit 2 alongside overcurrent
+    let data = [0x00, 0x00, 0x00, 0x06, 0x81, 0x01, 0x00, 0x00];
+    mppt.receive(&frame(ID_BROADCAST_STATUS, data)).unwrap();
+
+    let status = mppt.snapshot();
+    assert_eq!(status.raw_error_byte, Some(0x06));
+    assert_eq!(status.error_flags, Some(ErrorFlags::HW_OVERCURRENT));
+    assert_eq!(status.raw_limit_byte, Some(0x81));
+    assert_eq!(
+        status.limit_flags,
+        Some(LimitFlags::GLOBAL_MPPT | LimitFlags::INPUT_CURRENT_MIN)
+    );
+}
@@ -0,0 +1,40 @@
+//! Serializing the status with `serde`.
+#![cfg(feature = "serde")]
+
+mod common;
+
+use common::*;
+use elmar_mppt::*;
+
+#[test]
+fn status_round_trips_through_json() {
+    let status = golden();
+
+    let json = serde_json::to_string(&status).unwrap();
+    assert_eq!(serde_json::from_str::<Status>(&json).unwrap(), status);
+
+    let empty = serde_json::to_string(&Status::default()).unwrap();
+    assert_eq!(
+        serde_json::from_str::<Status>(&empty).unwrap(),
+        Status::default()
+    );
+}
+
+#[test]
+fn flags_are_serialized_as_raw_bits() {
+    let errors = ErrorFlags::HW_OVERCURRENT | ErrorFlags::BATTERY_LOW;
+    assert_eq!(serde_json::to_string(&errors).unwrap(), "34");
+    assert_eq!(serde_json::from_str::<ErrorFlags>("34").unwrap(), errors);
+    // the reserved bit is dropped
+    assert_eq!(serde_json::from_str::<ErrorFlags>("38").unwrap(), errors);
+
+    let limits = LimitFlags::INPUT_CURRENT_MAX;
+    assert_eq!(serde_json::to_string(&limits).unwrap(), "64");
+    assert_eq!(serde_json::from_str::<LimitFlags>("64").unwrap(), limits);
+
+    let mode = serde_json::to_string(&Mode::Unknown(2)).unwrap();
+    assert_eq!(
+        serde_json::from_str::<Mode>(&mode).unwrap(),
+        Mode::Unknown(2)
+    );
+}
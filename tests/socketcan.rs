@@ -0,0 +1,37 @@
+//! Conversions to and from SocketCAN frames.
+#![cfg(feature = "socketcan")]
+
+mod common;
+
+use common::*;
+use elmar_mppt::*;
+use socketcan::{CanFrame, EmbeddedFrame, StandardId};
+
+#[test]
+fn command_frame_round_trips_through_socketcan() {
+    let mut mppt = Mppt::new(ID_BASE);
+    let command = mppt.set_maximum_input_current(8.0).unwrap();
+
+    let converted = to_socketcan(&command);
+    assert_eq!(
+        converted.id(),
+        StandardId::new(ID_BASE + ID_COMMAND_MAX_INPUT_CURERNT)
+            .unwrap()
+            .into()
+    );
+    assert_eq!(converted.data(), command.data().unwrap().as_ref());
+
+    assert_eq!(from_socketcan(&converted), Some(command));
+}
+
+#[test]
+fn socketcan_frames_are_decoded() {
+    let mut mppt = Mppt::new(ID_BASE);
+    let input = to_socketcan(&frame(ID_BROADCAST_INPUT, floats(100.0, 2.5)));
+
+    assert_eq!(mppt.receive_socketcan(&input), Ok(Some(Broadcast::Input)));
+    assert_eq!(mppt.snapshot().input_voltage, Some(100.0));
+
+    let remote = CanFrame::new_remote(StandardId::new(ID_BASE).unwrap(), 8).unwrap();
+    assert_eq!(mppt.receive_socketcan(&remote), Err(Error::NotADataFrame));
+}
@@ -0,0 +1,358 @@
+//! Derived values and health checks on `Status`.
+
+mod common;
+
+use common::*;
+use elmar_mppt::*;
+
+#[test]
+fn average_over_snapshots() {
+    let history = [
+        Status {
+            input_voltage: Some(100.0),
+            output_current: Some(1.0),
+            mode: Some(Mode::Standby),
+            ..Default::default()
+        },
+        Status {
+            input_voltage: Some(110.0),
+            ..Default::default()
+        },
+        Status {
+            input_voltage: Some(120.0),
+            output_current: Some(2.0),
+            mode: Some(Mode::On),
+            ..Default::default()
+        },
+    ];
+
+    let average = average_over(&history);
+    assert_eq!(average.input_voltage, Some(110.0));
+    // only present values are averaged
+    assert_eq!(average.output_current, Some(1.5));
+    assert_eq!(average.output_voltage, None);
+    // other fields come from the newest snapshot
+    assert_eq!(average.mode, Some(Mode::On));
+
+    assert_eq!(average_over(&[]), Status::default());
+}
+
+#[test]
+fn nominal_and_degraded_states() {
+    // the golden device reports a full battery
+    assert_eq!(golden().is_nominal(), Some(false));
+    let healthy = Status {
+        error_flags: Some(ErrorFlags::empty()),
+        ..golden()
+    };
+    assert_eq!(healthy.is_nominal(), Some(true));
+    assert_eq!(Status::default().is_nominal(), None);
+
+    let degraded = [
+        Status {
+            error_flags: Some(ErrorFlags::BATTERY_LOW),
+            ..healthy
+        },
+        Status {
+            rail_3v: Some(2.5),
+            ..healthy
+        },
+        Status {
+            mostfet_temperature: Some(85.0),
+            ..healthy
+        },
+        // more power out than in
+        Status {
+            output_current: Some(3.0),
+            ..healthy
+        },
+    ];
+    for status in degraded {
+        assert_eq!(status.is_nominal(), Some(false), "{status:?}");
+    }
+}
+
+#[test]
+fn minimum_input_flag_set_and_clear() {
+    let pinned = Status {
+        input_current: Some(0.05),
+        limit_flags: Some(LimitFlags::INPUT_CURRENT_MIN),
+        ..golden()
+    };
+    assert_eq!(pinned.at_minimum_input(), Some(true));
+    assert_eq!(golden().at_minimum_input(), Some(false));
+
+    let unknown = Status {
+        input_current: None,
+        ..pinned
+    };
+    assert_eq!(unknown.at_minimum_input(), None);
+
+    // the flag is contradicted by the measured current
+    let stale_flag = Status {
+        input_current: Some(2.5),
+        ..pinned
+    };
+    assert_eq!(stale_flag.at_minimum_input(), Some(false));
+    let at_threshold = Status {
+        input_current: Some(INPUT_CURRENT_MIN_THRESHOLD),
+        ..pinned
+    };
+    assert_eq!(at_threshold.at_minimum_input(), Some(true));
+}
+
+#[test]
+fn control_rail_brownout_is_confirmed_by_the_rail() {
+    let flagged = Status {
+        error_flags: Some(ErrorFlags::UNDERVOLTAGE_12V),
+        rail_12v: Some(9.5),
+        ..golden()
+    };
+    assert_eq!(flagged.control_rail_brownout(), Some(true));
+
+    // the rail measurement recovered, so the flag is not confirmed
+    let recovered = Status {
+        rail_12v: Some(12.0),
+        ..flagged
+    };
+    assert_eq!(recovered.control_rail_brownout(), Some(false));
+
+    let unmeasured = Status {
+        rail_12v: None,
+        ..flagged
+    };
+    assert_eq!(unmeasured.control_rail_brownout(), Some(true));
+    assert_eq!(golden().control_rail_brownout(), Some(false));
+    assert_eq!(Status::default().control_rail_brownout(), None);
+}
+
+#[test]
+fn operating_point_classification() {
+    let healthy = Status {
+        error_flags: Some(ErrorFlags::empty()),
+        ..golden()
+    };
+    assert_eq!(healthy.operating_point(), Some(OperatingPoint::Harvesting));
+
+    let fault = Status {
+        error_flags: Some(ErrorFlags::MOSFET_OVERHEAT),
+        limit_flags: Some(LimitFlags::MOSFET_TEMPERATURE),
+        ..healthy
+    };
+    assert_eq!(fault.operating_point(), Some(OperatingPoint::Fault));
+
+    let idle = Status {
+        output_current: Some(0.0),
+        ..healthy
+    };
+    assert_eq!(idle.operating_point(), Some(OperatingPoint::Idle));
+
+    let limited = Status {
+        limit_flags: Some(LimitFlags::GLOBAL_MPPT | LimitFlags::OUTPUT_VOLTAGE_MAX),
+        ..healthy
+    };
+    assert_eq!(limited.operating_point(), Some(OperatingPoint::Limited));
+
+    assert_eq!(Status::default().operating_point(), None);
+}
+
+#[test]
+fn power_loss_rejects_impossible_results() {
+    // 250 W in, 240 W out
+    assert_eq!(golden().power_loss(), Some(10.0));
+
+    let impossible = Status {
+        output_current: Some(3.0),
+        ..golden()
+    };
+    assert_eq!(impossible.power_loss(), None);
+
+    let missing = Status {
+        output_voltage: None,
+        ..golden()
+    };
+    assert_eq!(missing.power_loss(), None);
+}
+
+#[test]
+fn fill_factor_against_references() {
+    // 250 W at the operating point of a 125 V, 2.5 A array
+    assert_eq!(golden().estimated_fill_factor(125.0, 2.5), Some(0.8));
+
+    assert_eq!(golden().estimated_fill_factor(0.0, 2.0), None);
+    assert_eq!(golden().estimated_fill_factor(125.0, 0.0), None);
+    assert_eq!(Status::default().estimated_fill_factor(125.0, 2.0), None);
+}
+
+#[test]
+fn temperature_sensor_agreement() {
+    // 40 and 35 degrees
+    assert_eq!(golden().temperature_sensors_agree(10.0), Some(true));
+    assert_eq!(golden().temperature_sensors_agree(2.0), Some(false));
+
+    let diverging = Status {
+        controller_temperature: Some(75.0),
+        ..golden()
+    };
+    assert_eq!(diverging.temperature_sensors_agree(10.0), Some(false));
+
+    let missing = Status {
+        controller_temperature: None,
+        ..golden()
+    };
+    assert_eq!(missing.temperature_sensors_agree(10.0), None);
+}
+
+#[test]
+fn validate_reports_every_issue() {
+    assert_eq!(golden().validate(), ValidationIssues::empty());
+    assert_eq!(Status::default().validate(), ValidationIssues::empty());
+
+    let broken = Status {
+        rail_3v: Some(f32::NAN),
+        input_voltage: Some(-1.0),
+        mostfet_temperature: Some(60.0),
+        controller_temperature: Some(20.0),
+        ..golden()
+    };
+    assert_eq!(
+        broken.validate(),
+        ValidationIssues::NON_FINITE
+            | ValidationIssues::OUT_OF_RANGE
+            | ValidationIssues::INCONSISTENT_POWER
+            | ValidationIssues::SENSOR_DISAGREEMENT
+    );
+}
+
+#[test]
+fn power_and_efficiency() {
+    let status = golden();
+    assert_eq!(status.input_power(), Some(250.0));
+    assert_eq!(status.output_power(), Some(240.0));
+    assert_eq!(status.efficiency(), Some(0.96));
+
+    let no_input = Status {
+        input_current: Some(0.0),
+        ..golden()
+    };
+    assert_eq!(no_input.input_power(), Some(0.0));
+    assert_eq!(no_input.efficiency(), None);
+
+    let missing = Status {
+        input_voltage: None,
+        output_current: None,
+        ..golden()
+    };
+    assert_eq!(missing.input_power(), None);
+    assert_eq!(missing.output_power(), None);
+    assert_eq!(missing.efficiency(), None);
+}
+
+#[test]
+fn fault_bytes_round_trip() {
+    let status = Status {
+        error_flags: Some(ErrorFlags::HW_OVERCURRENT | ErrorFlags::LOW_ARRAY_POWER),
+        limit_flags: Some(LimitFlags::INPUT_CURRENT_MAX),
+        ..golden()
+    };
+
+    let bytes = status.fault_bytes().unwrap();
+    assert_eq!(bytes, (0x82, 0x40));
+
+    let rebuilt = Status::from_fault_bytes(bytes);
+    assert_eq!(rebuilt.error_flags, status.error_flags);
+    assert_eq!(rebuilt.limit_flags, status.limit_flags);
+    assert_eq!(rebuilt.fault_bytes(), Some(bytes));
+    assert_eq!(rebuilt.input_voltage, None);
+
+    assert_eq!(Status::default().fault_bytes(), None);
+}
+
+#[test]
+fn output_disconnected_with_input_power() {
+    let disconnected = Status {
+        output_current: Some(0.0),
+        ..golden()
+    };
+    assert_eq!(disconnected.output_disconnected(50.0), Some(true));
+    assert_eq!(golden().output_disconnected(50.0), Some(false));
+
+    // not enough input power to tell
+    assert_eq!(disconnected.output_disconnected(500.0), Some(false));
+    assert_eq!(Status::default().output_disconnected(50.0), None);
+}
+
+#[test]
+fn connector_resistance_from_temperature_rise() {
+    let status = Status {
+        power_connector_temperature: Some(45.0),
+        output_current: Some(10.0),
+        ..golden()
+    };
+    // 20 degrees above ambient at 2 degrees per watt is 10 W, or 0.1 ohm at 10 A
+    let resistance = status.connector_resistance_estimate(25.0, 2.0).unwrap();
+    assert!((resistance - 0.1).abs() < 1e-6, "{resistance}");
+
+    assert_eq!(status.connector_resistance_estimate(50.0, 2.0), None);
+    assert_eq!(status.connector_resistance_estimate(25.0, 0.0), None);
+    assert_eq!(
+        Status::default().connector_resistance_estimate(25.0, 2.0),
+        None
+    );
+}
+
+#[test]
+fn charge_rate_in_c() {
+    let status = Status {
+        output_current: Some(10.0),
+        ..Default::default()
+    };
+    assert_eq!(status.charge_rate_c(20.0), Some(0.5));
+    assert_eq!(status.charge_rate_c(5.0), Some(2.0));
+    assert_eq!(golden().charge_rate_c(40.0), Some(0.0375));
+
+    assert_eq!(status.charge_rate_c(0.0), None);
+    assert_eq!(Status::default().charge_rate_c(20.0), None);
+}
+
+#[test]
+fn counters_are_ignored_when_comparing() {
+    let before = Status {
+        test_counter: Some(1),
+        can_rx_error_count: Some(0),
+        ..golden()
+    };
+    let after = Status {
+        test_counter: Some(2),
+        can_rx_error_count: Some(4),
+        ..before
+    };
+    assert_ne!(before, after);
+    assert!(before.eq_ignoring_counters(&after));
+
+    let changed = Status {
+        mode: Some(Mode::Standby),
+        ..after
+    };
+    assert!(!before.eq_ignoring_counters(&changed));
+}
+
+#[test]
+fn average_over_includes_commanded_setpoints() {
+    let history = [
+        Status {
+            commanded_max_output_voltage: Some(150.0),
+            commanded_max_input_current: Some(6.0),
+            ..Default::default()
+        },
+        Status {
+            commanded_max_output_voltage: Some(160.0),
+            commanded_max_input_current: None,
+            ..Default::default()
+        },
+    ];
+
+    let average = average_over(&history);
+    assert_eq!(average.commanded_max_output_voltage, Some(155.0));
+    assert_eq!(average.commanded_max_input_current, Some(6.0));
+}
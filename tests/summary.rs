@@ -0,0 +1,41 @@
+//! Short text summaries of the status.
+#![cfg(feature = "heapless")]
+
+use elmar_mppt::*;
+
+fn populated() -> Status {
+    Status {
+        mode: Some(Mode::On),
+        input_voltage: Some(100.0),
+        input_current: Some(5.0),
+        output_voltage: Some(151.24),
+        output_current: Some(3.2),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn summary_of_a_populated_status() {
+    let summary = populated().summary_string::<32>();
+    assert_eq!(summary.as_str(), "On 151.2V 3.2A 97%");
+}
+
+#[test]
+fn summary_marks_unknown_values() {
+    let status = Status {
+        mode: Some(Mode::Unknown(2)),
+        output_voltage: Some(12.0),
+        ..Default::default()
+    };
+    assert_eq!(status.summary_string::<32>().as_str(), "Mode 2 12.0V -A -%");
+    assert_eq!(
+        Status::default().summary_string::<32>().as_str(),
+        "- -V -A -%"
+    );
+}
+
+#[test]
+fn summary_is_cut_off_to_fit() {
+    assert_eq!(populated().summary_string::<9>().as_str(), "On 151.2V");
+    assert_eq!(populated().summary_string::<0>().as_str(), "");
+}
@@ -0,0 +1,389 @@
+//! Per device telemetry bookkeeping: staleness, resets and extremes.
+
+mod common;
+
+use bxcan::{Frame, StandardId};
+use common::*;
+use elmar_mppt::*;
+
+#[test]
+fn groups_go_stale_after_the_timeout() {
+    let mut mppt = Mppt::new(ID_BASE);
+    assert!(mppt.is_stale(Broadcast::Input, 0, 1000));
+
+    let input = frame(ID_BROADCAST_INPUT, floats(100.0, 2.5));
+    assert_eq!(mppt.receive_at(&input, 5000), Ok(Some(Broadcast::Input)));
+
+    assert!(!mppt.is_stale(Broadcast::Input, 5500, 1000));
+    assert!(!mppt.is_stale(Broadcast::Input, 6000, 1000));
+    assert!(mppt.is_stale(Broadcast::Input, 6001, 1000));
+    // other groups were never received
+    assert!(mppt.is_stale(Broadcast::Output, 5500, 1000));
+    assert!(mppt.is_stale(Broadcast::CommandMaxInputCurrent, 5500, 1000));
+
+    // the millisecond counter may wrap
+    mppt.receive_at(&input, u32::MAX - 100).unwrap();
+    assert!(!mppt.is_stale(Broadcast::Input, 200, 1000));
+}
+
+#[test]
+fn follow_moves_to_the_new_base_id() {
+    let mut mppt = Mppt::new(ID_BASE);
+    let input = frame(ID_BROADCAST_INPUT, floats(100.0, 2.5));
+    mppt.receive(&input).unwrap();
+
+    let moved = ID_BASE + 2 * ID_INC;
+    assert_eq!(mppt.follow(moved), Ok(()));
+    assert_eq!(mppt.base_id(), moved);
+    assert_eq!(mppt.snapshot(), Status::default());
+
+    assert!(!mppt.is_for_device(&input));
+    let at_new_id = Frame::new_data(StandardId::new(moved).unwrap(), floats(120.0, 3.0));
+    assert!(mppt.is_for_device(&at_new_id));
+    assert_eq!(mppt.receive(&at_new_id), Ok(Some(Broadcast::Input)));
+    assert_eq!(mppt.snapshot().input_voltage, Some(120.0));
+}
+
+#[test]
+fn follow_forgets_accumulated_values() {
+    let mut mppt = Mppt::new(ID_BASE).with_extremes(true);
+    // 100 W in and out for an hour
+    for now in [0, 3_600_000] {
+        mppt.receive_at(&frame(ID_BROADCAST_INPUT, floats(100.0, 1.0)), now)
+            .unwrap();
+        mppt.receive_at(&frame(ID_BROADCAST_OUTPUT, floats(100.0, 1.0)), now)
+            .unwrap();
+    }
+    assert_eq!(mppt.energy_harvested_wh(), 100.0);
+    mppt.set_maximum_input_current(8.0).unwrap();
+    receive(&mut mppt, ID_BROADCAST_LIMITS, floats(150.0, 8.0));
+    assert_eq!(mppt.input_current_limit_applied(0.1), Some(true));
+
+    mppt.follow(ID_BASE + ID_INC).unwrap();
+    assert_eq!(mppt.energy_harvested_wh(), 0.0);
+    assert_eq!(mppt.time_weighted_input_power(), None);
+    assert_eq!(mppt.extremes(), Extremes::default());
+
+    // the setpoint was commanded to the previous device
+    receive(&mut mppt, ID_BROADCAST_LIMITS, floats(150.0, 8.0));
+    assert_eq!(mppt.input_current_limit_applied(0.1), None);
+}
+
+fn status_frame(counter: u8) -> Frame {
+    frame(ID_BROADCAST_STATUS, status_payload(0, 1, counter))
+}
+
+#[test]
+fn reset_on_reboot_clears_stale_values() {
+    let mut mppt = Mppt::new(ID_BASE).with_reset_on_reboot(true);
+    mppt.receive(&frame(ID_BROADCAST_INPUT, floats(100.0, 2.5)))
+        .unwrap();
+    mppt.receive(&status_frame(100)).unwrap();
+
+    mppt.receive(&status_frame(3)).unwrap();
+    assert!(mppt.detected_reboot());
+    assert_eq!(mppt.snapshot().input_voltage, None);
+    // the frame showing the reboot is still applied
+    assert_eq!(mppt.snapshot().test_counter, Some(3));
+
+    // off by default
+    let mut kept = Mppt::new(ID_BASE);
+    kept.receive(&frame(ID_BROADCAST_INPUT, floats(100.0, 2.5)))
+        .unwrap();
+    kept.receive(&status_frame(100)).unwrap();
+    kept.receive(&status_frame(3)).unwrap();
+    assert!(kept.detected_reboot());
+    assert_eq!(kept.snapshot().input_voltage, Some(100.0));
+}
+
+#[test]
+fn timestamped_reboot_keeps_the_group_fresh() {
+    let mut mppt = Mppt::new(ID_BASE)
+        .with_reset_on_reboot(true)
+        .with_min_status_interval(100);
+    mppt.receive_at(&status_frame(100), 1000).unwrap();
+
+    assert_eq!(
+        mppt.receive_at(&status_frame(0), 1200),
+        Ok(Some(Broadcast::StatusFrame))
+    );
+    assert!(mppt.detected_reboot());
+    assert!(!mppt.is_stale(Broadcast::StatusFrame, 1200, 500));
+    assert!(mppt.is_online(1200, 500));
+
+    // the throttle is armed by the frame showing the reboot
+    let throttled = frame(ID_BROADCAST_STATUS, status_payload(0, 0, 1));
+    assert_eq!(mppt.receive_at(&throttled, 1250), Ok(None));
+    // only the counter is tracked for reboot detection
+    assert_eq!(mppt.snapshot().test_counter, Some(1));
+    assert_eq!(mppt.snapshot().mode, Some(Mode::On));
+}
+
+#[test]
+fn clearing_the_status_keeps_the_base_id() {
+    let mut status = Status {
+        input_voltage: Some(100.0),
+        error_flags: Some(ErrorFlags::BATTERY_FULL),
+        mode: Some(Mode::On),
+        test_counter: Some(7),
+        ..Default::default()
+    };
+    status.clear();
+    assert_eq!(status, Status::default());
+
+    let mut mppt = Mppt::new(ID_BASE + ID_INC);
+    let input = Frame::new_data(
+        StandardId::new(ID_BASE + ID_INC).unwrap(),
+        floats(100.0, 2.5),
+    );
+    mppt.receive_at(&input, 10).unwrap();
+
+    mppt.reset_status();
+    assert_eq!(mppt.snapshot(), Status::default());
+    assert!(mppt.is_stale(Broadcast::Input, 10, 1000));
+    assert_eq!(mppt.base_id(), ID_BASE + ID_INC);
+    assert_eq!(mppt.receive(&input), Ok(Some(Broadcast::Input)));
+}
+
+#[test]
+fn extremes_follow_a_rising_then_falling_input() {
+    let mut mppt = Mppt::new(ID_BASE).with_extremes(true);
+    assert_eq!(mppt.extremes().input_voltage, Extreme::default());
+
+    for voltage in [100.0, 110.0, 125.0, f32::NAN, 90.0, 95.0] {
+        mppt.receive(&frame(ID_BROADCAST_INPUT, floats(voltage, 2.5)))
+            .unwrap();
+    }
+
+    let input = mppt.extremes().input_voltage;
+    assert_eq!(input.min, Some(90.0));
+    assert_eq!(input.max, Some(125.0));
+    assert_eq!(mppt.extremes().output_voltage, Extreme::default());
+
+    mppt.reset_extremes();
+    mppt.receive(&frame(ID_BROADCAST_INPUT, floats(105.0, 2.5)))
+        .unwrap();
+    assert_eq!(mppt.extremes().input_voltage.min, Some(105.0));
+    assert_eq!(mppt.extremes().input_voltage.max, Some(105.0));
+
+    // not tracked unless enabled
+    let mut untracked = Mppt::new(ID_BASE);
+    untracked
+        .receive(&frame(ID_BROADCAST_INPUT, floats(100.0, 2.5)))
+        .unwrap();
+    assert_eq!(untracked.extremes().input_voltage, Extreme::default());
+}
+
+#[test]
+fn min_status_interval_drops_rapid_frames() {
+    let mut mppt = Mppt::new(ID_BASE).with_min_status_interval(1000);
+
+    // a broadcast every 200 ms with a rising voltage
+    let mut applied = Vec::new();
+    for step in 0..11u32 {
+        let input = frame(ID_BROADCAST_INPUT, floats(100.0 + step as f32, 2.5));
+        if mppt.receive_at(&input, step * 200).unwrap().is_some() {
+            applied.push(mppt.snapshot().input_voltage.unwrap());
+        }
+    }
+    assert_eq!(applied, [100.0, 105.0, 110.0]);
+
+    // dropped frames still keep the group fresh
+    assert!(!mppt.is_stale(Broadcast::Input, 2100, 200));
+
+    // groups are throttled independently
+    let output = frame(ID_BROADCAST_OUTPUT, floats(160.0, 1.0));
+    assert_eq!(mppt.receive_at(&output, 2100), Ok(Some(Broadcast::Output)));
+}
+
+#[test]
+fn time_weighted_input_power_weights_by_duration() {
+    // averaged over the whole session
+    let mut mppt = Mppt::new(ID_BASE).with_input_power_time_constant(0.0);
+    let input = |current| frame(ID_BROADCAST_INPUT, floats(100.0, current));
+
+    mppt.receive_at(&input(3.0), 0).unwrap();
+    assert_eq!(mppt.time_weighted_input_power(), None);
+
+    // a burst at 300 W followed by a long stretch at 100 W
+    mppt.receive_at(&input(3.0), 500).unwrap();
+    mppt.receive_at(&input(1.0), 1000).unwrap();
+    mppt.receive_at(&input(1.0), 10_000).unwrap();
+
+    // 300 W for 1 s and 100 W for 9 s, where the plain mean would be 200 W
+    assert_eq!(mppt.time_weighted_input_power(), Some(120.0));
+
+    // untimed frames do not contribute
+    mppt.receive(&input(10.0)).unwrap();
+    assert_eq!(mppt.time_weighted_input_power(), Some(120.0));
+}
+
+#[test]
+fn time_weighted_input_power_forgets_old_readings() {
+    let mut mppt = Mppt::new(ID_BASE).with_input_power_time_constant(60.0);
+    let input = |current| frame(ID_BROADCAST_INPUT, floats(100.0, current));
+
+    // an hour at 300 W, then ten minutes at 100 W
+    mppt.receive_at(&input(3.0), 0).unwrap();
+    mppt.receive_at(&input(1.0), 3_600_000).unwrap();
+    assert!((mppt.time_weighted_input_power().unwrap() - 300.0).abs() < 0.01);
+
+    mppt.receive_at(&input(1.0), 4_200_000).unwrap();
+    let recent = mppt.time_weighted_input_power().unwrap();
+    assert!((recent - 100.0).abs() < 0.1, "{}", recent);
+
+    // one time constant after the step, the old reading still weighs 1/e
+    let mut step = Mppt::new(ID_BASE).with_input_power_time_constant(60.0);
+    step.receive_at(&input(3.0), 0).unwrap();
+    step.receive_at(&input(1.0), 3_600_000).unwrap();
+    step.receive_at(&input(1.0), 3_660_000).unwrap();
+    let expected = 100.0 + 200.0 / core::f32::consts::E;
+    let average = step.time_weighted_input_power().unwrap();
+    assert!((average - expected).abs() < 0.1, "{}", average);
+}
+
+#[test]
+fn thermal_fault_is_extrapolated_from_a_rising_series() {
+    let mut mppt = Mppt::new(ID_BASE);
+    let temperature = |mosfet| frame(ID_BROADCAST_TEMPERATURE, floats(mosfet, 35.0));
+
+    mppt.receive_at(&temperature(40.0), 0).unwrap();
+    assert_eq!(mppt.time_to_thermal_fault(80.0), None);
+
+    // rising by 0.5 °C per second
+    mppt.receive_at(&temperature(45.0), 10_000).unwrap();
+    mppt.receive_at(&temperature(50.0), 20_000).unwrap();
+    assert_eq!(mppt.time_to_thermal_fault(80.0), Some(60));
+    assert_eq!(mppt.time_to_thermal_fault(50.0), Some(0));
+
+    // stable, then cooling
+    mppt.receive_at(&temperature(50.0), 30_000).unwrap();
+    assert_eq!(mppt.time_to_thermal_fault(80.0), None);
+    assert_eq!(mppt.time_to_thermal_fault(50.0), Some(0));
+    mppt.receive_at(&temperature(48.0), 40_000).unwrap();
+    assert_eq!(mppt.time_to_thermal_fault(80.0), None);
+    // still over the limit while cooling
+    assert_eq!(mppt.time_to_thermal_fault(45.0), Some(0));
+}
+
+#[test]
+fn observation_of_a_status_frame_setting_a_fault() {
+    let mut mppt = Mppt::new(ID_BASE);
+    let status = |errors, counter| frame(ID_BROADCAST_STATUS, [0, 0, 0, errors, 0, 1, 0, counter]);
+
+    let first = mppt.observe(&status(0x10, 1), 1000).unwrap();
+    assert_eq!(first.group, Some(Broadcast::StatusFrame));
+    assert!(first.changed);
+    assert_eq!(first.new_faults, ErrorFlags::BATTERY_FULL);
+    assert_eq!(first.previous_update, None);
+
+    // only the counter moved
+    let repeat = mppt.observe(&status(0x10, 2), 1200).unwrap();
+    assert!(!repeat.changed);
+    assert_eq!(repeat.new_faults, ErrorFlags::empty());
+    assert_eq!(repeat.previous_update, Some(1000));
+
+    let fault = mppt.observe(&status(0x50, 3), 1400).unwrap();
+    assert!(fault.changed);
+    assert_eq!(fault.new_faults, ErrorFlags::MOSFET_OVERHEAT);
+    assert_eq!(fault.previous_update, Some(1200));
+
+    let ignored = Frame::new_data(StandardId::new(ID_BASE + ID_INC).unwrap(), [0; 8]);
+    assert_eq!(
+        mppt.observe(&ignored, 1600),
+        Ok(Observation {
+            group: None,
+            changed: false,
+            new_faults: ErrorFlags::empty(),
+            previous_update: None,
+        })
+    );
+}
+
+#[test]
+fn change_deadband_hides_jitter() {
+    let mut mppt = Mppt::new(ID_BASE).with_change_deadband(0.5);
+    let mut observe = |voltage, now| {
+        let input = frame(ID_BROADCAST_INPUT, floats(voltage, 2.5));
+        mppt.observe(&input, now).unwrap().changed
+    };
+
+    assert!(observe(100.0, 0));
+    // within the deadband of the reported 100 V
+    assert!(!observe(100.25, 100));
+    assert!(!observe(100.5, 200));
+    assert!(observe(101.0, 300));
+
+    // a slow drift is reported once it exceeds the deadband
+    assert!(!observe(101.25, 400));
+    assert!(!observe(101.5, 500));
+    assert!(observe(101.75, 600));
+
+    // without a deadband any movement is a change
+    let mut exact = Mppt::new(ID_BASE);
+    exact
+        .observe(&frame(ID_BROADCAST_INPUT, floats(100.0, 2.5)), 0)
+        .unwrap();
+    let jitter = exact
+        .observe(&frame(ID_BROADCAST_INPUT, floats(100.25, 2.5)), 100)
+        .unwrap();
+    assert!(jitter.changed);
+}
+
+#[test]
+fn fault_onsets_are_kept_in_order() {
+    let mut mppt = Mppt::new(ID_BASE).with_reset_on_reboot(true);
+    let faults = |errors: ErrorFlags, counter: u8| {
+        frame(
+            ID_BROADCAST_STATUS,
+            status_payload(errors.bits(), 1, counter),
+        )
+    };
+    assert_eq!(mppt.recent_faults().count(), 0);
+
+    mppt.receive_at(&faults(ErrorFlags::BATTERY_LOW, 1), 1000)
+        .unwrap();
+    // a fault that stays set is not a new onset
+    mppt.receive_at(&faults(ErrorFlags::BATTERY_LOW, 2), 1200)
+        .unwrap();
+    mppt.receive_at(&faults(ErrorFlags::empty(), 3), 1400)
+        .unwrap();
+    let both = ErrorFlags::BATTERY_LOW | ErrorFlags::MOSFET_OVERHEAT;
+    mppt.receive_at(&faults(both, 4), 1600).unwrap();
+    mppt.receive_at(&faults(both | ErrorFlags::HW_OVERCURRENT, 5), 1800)
+        .unwrap();
+
+    let events: Vec<_> = mppt.recent_faults().collect();
+    assert_eq!(
+        events,
+        [
+            (1000, ErrorFlags::BATTERY_LOW),
+            (1600, both),
+            (1800, ErrorFlags::HW_OVERCURRENT)
+        ]
+    );
+
+    // kept across a reboot reset
+    mppt.receive_at(&faults(ErrorFlags::empty(), 0), 2000)
+        .unwrap();
+    assert!(mppt.detected_reboot());
+    assert_eq!(mppt.recent_faults().count(), 3);
+
+    // only the most recent onsets are kept
+    for i in 0..FAULT_HISTORY_LEN as u32 {
+        let errors = ErrorFlags::LOW_ARRAY_POWER;
+        mppt.receive_at(&faults(errors, 10 + 2 * i as u8), 3000 + i * 400)
+            .unwrap();
+        mppt.receive_at(
+            &faults(ErrorFlags::empty(), 11 + 2 * i as u8),
+            3200 + i * 400,
+        )
+        .unwrap();
+    }
+    let events: Vec<_> = mppt.recent_faults().collect();
+    assert_eq!(events.len(), FAULT_HISTORY_LEN);
+    assert_eq!(events[0], (3000, ErrorFlags::LOW_ARRAY_POWER));
+
+    mppt.follow(ID_BASE + ID_INC).unwrap();
+    assert_eq!(mppt.recent_faults().count(), 0);
+}